@@ -0,0 +1,49 @@
+//! With the `embedded_assets` feature, list every file in `assets/` for
+//! `EmbeddedAssetIo` to bake into the executable, so fonts, string tables
+//! and sprite sheets registered with the asset plugin are embedded without
+//! keeping a second list of them.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+fn main() {
+    println!("cargo:rerun-if-changed=assets");
+    let assets = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("assets");
+    let mut files = Vec::new();
+    if env::var_os("CARGO_FEATURE_EMBEDDED_ASSETS").is_some() {
+        collect_files(&assets, &mut files);
+        files.sort();
+    }
+
+    let mut source = String::from("&[\n");
+    for file in files {
+        let key = file
+            .strip_prefix(&assets)
+            .unwrap()
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        source.push_str(&format!(
+            "    ({:?}, include_bytes!({:?}).as_slice()),\n",
+            key, file
+        ));
+    }
+    source.push(']');
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded_files.rs");
+    fs::write(out, source).unwrap();
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{AssetManager, GameMode};
+use bevy::{prelude::*, utils::HashMap};
+
+/// How a clip behaves when it reaches its last frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationMode {
+    /// Jump back to the first frame.
+    Loop,
+    /// Run backwards to the first frame, then forwards again.
+    PingPong,
+    /// Stop on the last frame and report the clip as finished.
+    Once,
+}
+
+/// A named sequence of atlas frames, registered with the asset manager.
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub frames: Vec<usize>,
+    /// Time each frame is displayed, in milliseconds.
+    pub frame_duration: f32,
+    pub mode: AnimationMode,
+}
+
+/// Plays an `AnimationClip` (looked up by name in the `AssetManager`) on
+/// an entity's `TextureAtlasSprite`.
+#[derive(Component)]
+pub struct Animation {
+    clip: String,
+    frame: usize,
+    elapsed: f32,
+    forward: bool,
+    finished: bool,
+}
+
+impl Animation {
+    pub fn new<S: ToString>(clip: S) -> Self {
+        Self {
+            clip: clip.to_string(),
+            frame: 0,
+            elapsed: 0.0,
+            forward: true,
+            finished: false,
+        }
+    }
+
+    /// Start playing a clip from its first frame.
+    pub fn play<S: ToString>(&mut self, clip: S) {
+        *self = Self::new(clip);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn step(&mut self, clip: &AnimationClip) {
+        let last = clip.frames.len().saturating_sub(1);
+        match clip.mode {
+            AnimationMode::Loop => {
+                self.frame = if self.frame >= last {
+                    0
+                } else {
+                    self.frame + 1
+                };
+            }
+            AnimationMode::Once => {
+                if self.frame >= last {
+                    self.finished = true;
+                } else {
+                    self.frame += 1;
+                }
+            }
+            AnimationMode::PingPong => {
+                if last == 0 {
+                    return;
+                }
+                if self.forward && self.frame >= last {
+                    self.forward = false;
+                } else if !self.forward && self.frame == 0 {
+                    self.forward = true;
+                }
+                if self.forward {
+                    self.frame += 1;
+                } else {
+                    self.frame -= 1;
+                }
+            }
+        }
+    }
+}
+
+/// The states an animated entity can be in. Each state maps to a clip.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnimationState {
+    Idle,
+    Thrusting,
+    Hit,
+}
+
+/// Picks the clip an `Animation` plays. Systems `set` the ongoing state
+/// (e.g. thrusting) and `trigger` one-off states (e.g. being hit); a
+/// triggered state plays its clip through before returning to the ongoing
+/// state.
+#[derive(Component)]
+pub struct AnimationStateMachine {
+    clips: HashMap<AnimationState, String>,
+    state: AnimationState,
+    base: AnimationState,
+    triggered: bool,
+}
+
+impl AnimationStateMachine {
+    pub fn new() -> Self {
+        Self {
+            clips: HashMap::default(),
+            state: AnimationState::Idle,
+            base: AnimationState::Idle,
+            triggered: false,
+        }
+    }
+
+    pub fn with_state<S: ToString>(mut self, state: AnimationState, clip: S) -> Self {
+        self.clips.insert(state, clip.to_string());
+        self
+    }
+
+    /// The clip for the starting (idle) state, for building the `Animation`.
+    pub fn initial_animation(&self) -> Animation {
+        Animation::new(
+            self.clips
+                .get(&AnimationState::Idle)
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Set the ongoing state. Doesn't interrupt a triggered clip.
+    pub fn set(&mut self, state: AnimationState) {
+        self.base = state;
+    }
+
+    /// Play a state's clip immediately, restarting it if already playing.
+    pub fn trigger(&mut self, state: AnimationState) {
+        self.state = state;
+        self.triggered = true;
+    }
+}
+
+impl Default for AnimationStateMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn update_state_machines(
+    assets: Res<AssetManager>,
+    mut query: Query<(&mut AnimationStateMachine, &mut Animation)>,
+) {
+    for (mut machine, mut animation) in query.iter_mut() {
+        if machine.triggered {
+            machine.triggered = false;
+        } else if machine.state != machine.base {
+            let one_shot_playing = assets
+                .clips
+                .get(&animation.clip)
+                .map(|clip| clip.mode == AnimationMode::Once && !animation.is_finished())
+                .unwrap_or(false);
+            if one_shot_playing {
+                continue;
+            }
+            machine.state = machine.base;
+        } else {
+            continue;
+        }
+
+        if let Some(clip) = machine.clips.get(&machine.state) {
+            animation.play(clip);
+        }
+    }
+}
+
+fn animate_sprites(
+    time: Res<Time>,
+    assets: Res<AssetManager>,
+    mut query: Query<(&mut Animation, &mut TextureAtlasSprite)>,
+) {
+    let delta = time.delta().as_millis() as f32;
+    query.iter_mut().for_each(|(mut animation, mut sprite)| {
+        if let Some(clip) = assets.clips.get(&animation.clip) {
+            if clip.frames.is_empty() {
+                return;
+            }
+            animation.elapsed += delta;
+            while clip.frame_duration > 0.0
+                && animation.elapsed >= clip.frame_duration
+                && !animation.finished
+            {
+                animation.elapsed -= clip.frame_duration;
+                animation.step(clip);
+            }
+            sprite.index = clip.frames[animation.frame.min(clip.frames.len() - 1)];
+        }
+    });
+}
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameMode::Playing)
+                .with_system(update_state_machines)
+                .with_system(animate_sprites),
+        );
+    }
+}
@@ -1,7 +1,59 @@
-use bevy::prelude::*;
+use super::{LoadedFont, PackedAtlas, SpriteClipInfo};
+use crate::{AnimationClip, ScreenAsset};
+use bevy::{prelude::*, utils::HashMap};
 
 pub struct AssetManager {
-    pub menu_atlas: Handle<TextureAtlas>,
     pub default_font: Handle<Font>,
+    /// The default font followed by the fallback fonts, once loaded.
+    pub fonts: Vec<LoadedFont>,
     pub atlases: Vec<Handle<TextureAtlas>>,
+    pub clips: HashMap<String, AnimationClip>,
+    pub screens: HashMap<String, ScreenAsset>,
+    pub sprites: HashMap<String, usize>,
+    pub packed_atlases: Vec<PackedAtlas>,
+    /// Clips whose frames are sprite names, kept so they can be resolved
+    /// again when a packed atlas is rebuilt.
+    pub(super) sprite_clips: HashMap<String, SpriteClipInfo>,
+}
+
+impl AssetManager {
+    /// The atlas index of a named sprite from a packed atlas. An unknown
+    /// name is logged and shows the first sprite instead.
+    pub fn sprite_index(&self, name: &str) -> usize {
+        self.sprites.get(name).copied().unwrap_or_else(|| {
+            warn!("No sprite named \"{}\" has been loaded.", name);
+            0
+        })
+    }
+
+    /// Look up the frames of every clip registered by sprite name, adding
+    /// them to (or replacing them in) `clips`.
+    pub(super) fn resolve_sprite_clips(&mut self) {
+        let resolved: Vec<(String, AnimationClip)> = self
+            .sprite_clips
+            .iter()
+            .map(|(name, clip)| {
+                let clip = AnimationClip {
+                    frames: clip
+                        .frames
+                        .iter()
+                        .map(|frame| self.sprite_index(frame))
+                        .collect(),
+                    frame_duration: clip.frame_duration,
+                    mode: clip.mode,
+                };
+                (name.clone(), clip)
+            })
+            .collect();
+        self.clips.extend(resolved);
+    }
+
+    /// The first font that can draw all of `text`, trying the default font
+    /// before the fallbacks.
+    pub fn font_for(&self, text: &str) -> Handle<Font> {
+        self.fonts
+            .iter()
+            .find(|font| font.covers(text))
+            .map_or_else(|| self.default_font.clone(), |font| font.handle.clone())
+    }
 }
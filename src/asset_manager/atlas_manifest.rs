@@ -0,0 +1,26 @@
+//! The manifest written by the `pack_atlas` tool alongside a packed
+//! sprite sheet. Shared between the game and the tool.
+
+use bevy::reflect::TypeUuid;
+use serde::{Deserialize, Serialize};
+
+/// A named rectangle within a packed sprite sheet, in pixels.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AtlasSprite {
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Describes a packed sprite sheet: the image (relative to the manifest)
+/// and where each named sprite lives within it.
+#[derive(Clone, Debug, Serialize, Deserialize, TypeUuid)]
+#[uuid = "5c2a3d6e-8f41-4b1e-9f0a-7d3c2b1a6e54"]
+pub struct AtlasManifest {
+    pub texture: String,
+    pub width: u32,
+    pub height: u32,
+    pub sprites: Vec<AtlasSprite>,
+}
@@ -0,0 +1,69 @@
+use bevy::{
+    asset::{create_platform_default_asset_io, AssetIo, AssetIoError},
+    prelude::*,
+    utils::{BoxedFuture, HashMap},
+};
+use std::path::{Path, PathBuf};
+
+/// Every asset baked into the executable by the `embedded_assets` feature:
+/// the whole `assets/` folder, keyed by the path the game loads each file
+/// with. Listed by `build.rs`.
+const EMBEDDED_FILES: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/embedded_files.rs"));
+
+/// An `AssetIo` that serves embedded files from memory, falling back to
+/// the platform's usual asset source for anything that isn't embedded.
+pub struct EmbeddedAssetIo {
+    files: HashMap<PathBuf, &'static [u8]>,
+    fallback: Box<dyn AssetIo>,
+}
+
+impl AssetIo for EmbeddedAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        if let Some(bytes) = self.files.get(path) {
+            Box::pin(async move { Ok(bytes.to_vec()) })
+        } else {
+            self.fallback.load_path(path)
+        }
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        self.fallback.read_directory(path)
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        self.fallback.is_directory(path)
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        if self.files.contains_key(path) {
+            Ok(())
+        } else {
+            self.fallback.watch_path_for_changes(path)
+        }
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        self.fallback.watch_for_changes()
+    }
+}
+
+/// Installs the `EmbeddedAssetIo`. It has to be added before Bevy's
+/// `AssetPlugin`, which otherwise creates a file-backed `AssetServer`.
+pub struct EmbeddedAssetIoPlugin;
+
+impl Plugin for EmbeddedAssetIoPlugin {
+    fn build(&self, app: &mut App) {
+        let task_pool = app.world.resource::<bevy::tasks::IoTaskPool>().0.clone();
+        let io = EmbeddedAssetIo {
+            files: EMBEDDED_FILES
+                .iter()
+                .map(|(path, bytes)| (PathBuf::from(path), *bytes))
+                .collect(),
+            fallback: create_platform_default_asset_io(app),
+        };
+        app.insert_resource(AssetServer::new(io, task_pool));
+    }
+}
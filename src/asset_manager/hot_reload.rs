@@ -0,0 +1,108 @@
+use super::{build_packed_atlas, manifest_texture_path, register_sprite_names, AtlasManifest};
+use crate::{AssetManager, ScreenFill};
+use bevy::prelude::*;
+
+/// Ask the asset server to watch the asset folder. Only assets loaded
+/// after this call are watched, so it runs as a startup system, ahead of
+/// `setup_loading`.
+pub fn enable_hot_reload(asset_server: Res<AssetServer>) {
+    if let Err(e) = asset_server.watch_for_changes() {
+        warn!("Unable to watch assets for changes: {:?}", e);
+    }
+}
+
+/// When a source image changes on disk, rebuild every grid atlas that uses it.
+/// The tile size is kept, and the grid is re-derived from the new image
+/// dimensions - so adding a column of sprites doesn't need a restart.
+/// Atlases are replaced in place, so live sprites keep their handles.
+pub fn rebuild_modified_atlases(
+    mut events: EventReader<AssetEvent<Image>>,
+    assets: Option<Res<AssetManager>>,
+    images: Res<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let assets = if let Some(assets) = assets {
+        assets
+    } else {
+        return;
+    };
+
+    for event in events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            let image_size = if let Some(image) = images.get(handle) {
+                image.size()
+            } else {
+                continue;
+            };
+
+            let screen_atlases = assets
+                .screens
+                .values()
+                .filter_map(|screen| match &screen.fill {
+                    ScreenFill::Atlas { atlas, .. } => Some(atlas),
+                    _ => None,
+                });
+            let grid_atlases = assets.atlases.iter().filter(|atlas| {
+                !assets
+                    .packed_atlases
+                    .iter()
+                    .any(|packed| &packed.atlas == *atlas)
+            });
+            for atlas_handle in grid_atlases.chain(screen_atlases) {
+                if let Some(atlas) = texture_atlases.get_mut(atlas_handle) {
+                    if &atlas.texture != handle || atlas.textures.is_empty() {
+                        continue;
+                    }
+                    let tile_size = atlas.textures[0].size();
+                    let columns = ((image_size.x / tile_size.x) as usize).max(1);
+                    let rows = ((image_size.y / tile_size.y) as usize).max(1);
+                    *atlas = TextureAtlas::from_grid(handle.clone(), tile_size, columns, rows);
+                    info!("Rebuilt a {}x{} texture atlas", columns, rows);
+                }
+            }
+        }
+    }
+}
+
+/// When a packed atlas manifest changes (the `pack_atlas` tool was re-run),
+/// rebuild its atlas and sprite names in place, and look up the frames of
+/// clips registered by sprite name again.
+pub fn rebuild_modified_packed_atlases(
+    mut events: EventReader<AssetEvent<AtlasManifest>>,
+    assets: Option<ResMut<AssetManager>>,
+    manifests: Res<Assets<AtlasManifest>>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let mut assets = if let Some(assets) = assets {
+        assets
+    } else {
+        return;
+    };
+
+    for event in events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            let manifest = if let Some(manifest) = manifests.get(handle) {
+                manifest
+            } else {
+                continue;
+            };
+            let packed = assets
+                .packed_atlases
+                .iter()
+                .position(|packed| &packed.manifest == handle);
+            if let Some(index) = packed {
+                let manifest_path = assets.packed_atlases[index].manifest_path.clone();
+                let atlas_handle = assets.packed_atlases[index].atlas.clone();
+                let texture =
+                    asset_server.load(manifest_texture_path(&manifest_path, manifest).as_str());
+                if let Some(atlas) = texture_atlases.get_mut(&atlas_handle) {
+                    *atlas = build_packed_atlas(texture, manifest);
+                }
+                register_sprite_names(&mut assets, index, manifest);
+                assets.resolve_sprite_clips();
+                info!("Rebuilt packed atlas {}", manifest_path);
+            }
+        }
+    }
+}
@@ -1,11 +1,23 @@
+use super::{
+    build_packed_atlas, font_covers, manifest_texture_path, register_sprite_names, AtlasInfo,
+    AtlasManifest, Language, LoadedFont, Localization, PackedAtlas, StringTable,
+};
 use crate::{
     centered_text::centered_text_bundle, remove_matching_elements, AssetManager, BasicAssetPlugin,
-    GameMode,
+    GameMode, ScreenCommand,
 };
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 
 pub struct LoaderStatus {
     pub remaining_assets: Vec<HandleUntyped>,
+    /// Packed atlases (indices into `AssetManager::packed_atlases`) waiting
+    /// for their manifest, which names the image to load.
+    pub pending_packed_atlases: Vec<usize>,
+    /// String tables by language code, moved into `Localization` as they
+    /// arrive.
+    pub pending_languages: Vec<(String, Handle<StringTable>)>,
+    /// The default font, then the fallbacks.
+    pub fonts: Vec<Handle<Font>>,
 }
 
 #[derive(Component)]
@@ -17,45 +29,93 @@ pub fn setup_loading(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     asset_requests: Res<BasicAssetPlugin>,
 ) {
-    // Load the required font
-    let default_font = asset_server.load("FiraMono-Medium.ttf");
+    // Load the required font, and any fallbacks for other scripts
+    let default_font: Handle<Font> = asset_server.load("FiraMono-Medium.ttf");
+    let mut fonts = vec![default_font.clone()];
+    fonts.extend(
+        asset_requests
+            .fallback_fonts
+            .iter()
+            .map(|filename| asset_server.load(filename.as_str())),
+    );
 
     // Initialize the loading list
     let mut status = LoaderStatus {
-        remaining_assets: Vec::new(),
+        remaining_assets: fonts.iter().map(|font| font.clone_untyped()).collect(),
+        pending_packed_atlases: Vec::new(),
+        pending_languages: Vec::new(),
+        fonts,
     };
 
-    // Load the main menu and game over atlas
-    let menu_texture_handle = asset_server.load("menus.png");
-    status
-        .remaining_assets
-        .push(menu_texture_handle.clone_untyped());
-    let menu_atlas = TextureAtlas::from_grid(menu_texture_handle, Vec2::new(1024.0, 768.0), 2, 1);
-    let menu_atlas_handle = texture_atlases.add(menu_atlas);
+    // Load the string tables
+    for (code, filename) in asset_requests.languages.iter() {
+        let table: Handle<StringTable> = asset_server.load(filename.as_str());
+        status.remaining_assets.push(table.clone_untyped());
+        status.pending_languages.push((code.clone(), table));
+    }
+
+    // Load the registered screen backgrounds
+    let screens = asset_requests
+        .screens
+        .iter()
+        .map(|(name, background)| {
+            let screen = background.load(
+                &asset_server,
+                &mut texture_atlases,
+                &mut status.remaining_assets,
+            );
+            (name.clone(), screen)
+        })
+        .collect();
 
     // Load the requested teture atlases
     let mut atlases = Vec::new();
+    let mut packed_atlases = Vec::new();
     for atlas in asset_requests.atlases.iter() {
-        let texture_handle = asset_server.load(&atlas.filename);
-        status.remaining_assets.push(texture_handle.clone_untyped());
-        let atlas = TextureAtlas::from_grid(
-            texture_handle,
-            Vec2::new(atlas.sprite_width, atlas.sprite_height),
-            atlas.columns,
-            atlas.rows,
-        );
-        let atlas_handle = texture_atlases.add(atlas);
+        let atlas_handle = match atlas {
+            AtlasInfo::Grid {
+                filename,
+                sprite_width,
+                sprite_height,
+                columns,
+                rows,
+            } => {
+                let texture_handle = asset_server.load(filename.as_str());
+                status.remaining_assets.push(texture_handle.clone_untyped());
+                let atlas = TextureAtlas::from_grid(
+                    texture_handle,
+                    Vec2::new(*sprite_width, *sprite_height),
+                    *columns,
+                    *rows,
+                );
+                texture_atlases.add(atlas)
+            }
+            AtlasInfo::Packed { manifest } => {
+                // The atlas is filled in by `run_loading` once the manifest arrives.
+                let manifest_handle: Handle<AtlasManifest> = asset_server.load(manifest.as_str());
+                status
+                    .remaining_assets
+                    .push(manifest_handle.clone_untyped());
+                let atlas_handle =
+                    texture_atlases.add(TextureAtlas::new_empty(Handle::default(), Vec2::ONE));
+                status.pending_packed_atlases.push(packed_atlases.len());
+                packed_atlases.push(PackedAtlas {
+                    manifest: manifest_handle,
+                    manifest_path: manifest.clone(),
+                    atlas: atlas_handle.clone(),
+                    sprite_names: Vec::new(),
+                });
+                atlas_handle
+            }
+        };
         atlases.push(atlas_handle);
     }
 
-    // Create the basic loading UI
-    commands
-        .spawn_bundle(OrthographicCameraBundle::new_2d())
-        .insert(LoaderElement);
-
+    // Create the basic loading UI. The message is translated once the
+    // string tables arrive.
     commands
         .spawn_bundle(centered_text_bundle(
-            "Loading, Please Wait...",
+            "",
             default_font.clone(),
             30.0,
             Color::WHITE,
@@ -65,28 +125,108 @@ pub fn setup_loading(
 
     // Make the resource available
     commands.insert_resource(AssetManager {
-        menu_atlas: menu_atlas_handle,
         default_font,
+        fonts: Vec::new(),
         atlases,
+        clips: asset_requests.clips.clone(),
+        screens,
+        sprites: HashMap::default(),
+        packed_atlases,
+        sprite_clips: asset_requests.sprite_clips.clone(),
     });
     commands.insert_resource(status);
+    commands.insert_resource(Localization::default());
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn run_loading(
-    mut app_state: ResMut<State<GameMode>>,
+    mut screen_commands: EventWriter<ScreenCommand>,
     mut loader: ResMut<LoaderStatus>,
     server: Res<AssetServer>,
+    mut assets: ResMut<AssetManager>,
+    manifests: Res<Assets<AtlasManifest>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut localization: ResMut<Localization>,
+    string_tables: Res<Assets<StringTable>>,
+    fonts: Res<Assets<Font>>,
+    mut loading_text: Query<&mut Text, With<LoaderElement>>,
 ) {
+    // Take the string tables as they arrive, in the order they were
+    // registered, so the first stays the fallback.
+    while let Some((code, handle)) = loader.pending_languages.first() {
+        if let Some(table) = string_tables.get(handle) {
+            localization.languages.push(Language {
+                code: code.clone(),
+                table: table.clone(),
+            });
+            loader.pending_languages.remove(0);
+        } else {
+            break;
+        }
+    }
+
+    // Show the loading message in whichever language we have, and in a
+    // font that can draw it once the fonts are in.
+    let message = localization.text("loading");
+    for mut text in loading_text.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+        let font = loader.fonts.iter().find(|handle| {
+            fonts
+                .get(*handle)
+                .is_some_and(|font| font_covers(&font.font, &message))
+        });
+        if let Some(font) = font {
+            if text.sections[0].style.font != *font {
+                text.sections[0].style.font = font.clone();
+            }
+        }
+    }
+
+    // Build any packed atlases whose manifests have arrived, and start
+    // loading their images.
+    let mut still_pending = Vec::new();
+    for index in loader.pending_packed_atlases.drain(..).collect::<Vec<_>>() {
+        let packed = &assets.packed_atlases[index];
+        if let Some(manifest) = manifests.get(&packed.manifest) {
+            let texture_handle =
+                server.load(manifest_texture_path(&packed.manifest_path, manifest).as_str());
+            loader.remaining_assets.push(texture_handle.clone_untyped());
+            if let Some(atlas) = texture_atlases.get_mut(&packed.atlas) {
+                *atlas = build_packed_atlas(texture_handle, manifest);
+            }
+            register_sprite_names(&mut assets, index, manifest);
+        } else {
+            still_pending.push(index);
+        }
+    }
+    loader.pending_packed_atlases = still_pending;
+
     // Remove any assets from the list that have loaded
     loader.remaining_assets.retain(|h| {
         let state = server.get_load_state(h.id);
         state != bevy::asset::LoadState::Loaded
     });
 
-    if loader.remaining_assets.is_empty() {
-        app_state
-            .set(GameMode::MainMenu)
-            .expect("Unable to change game mode.");
+    if loader.remaining_assets.is_empty()
+        && loader.pending_packed_atlases.is_empty()
+        && loader.pending_languages.is_empty()
+    {
+        assets.fonts = loader
+            .fonts
+            .iter()
+            .filter_map(|handle| {
+                fonts
+                    .get(handle)
+                    .map(|font| LoadedFont::new(handle.clone(), font))
+            })
+            .collect();
+
+        // Sprite names are known now, so named clips can be resolved
+        assets.resolve_sprite_clips();
+
+        screen_commands.send(ScreenCommand::Replace(GameMode::MainMenu));
     }
 }
 
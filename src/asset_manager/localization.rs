@@ -0,0 +1,126 @@
+use ab_glyph::{Font as _, FontArc};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+/// One language's strings, loaded from a `.lang.ron` file. Strings may
+/// contain `{name}` placeholders, filled in by `Localization::format`.
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "0b7c51e2-94d3-4f0a-a6c8-3e1d27f95b40"]
+pub struct StringTable {
+    /// The language's own name for itself, shown on the options screen.
+    pub name: String,
+    pub strings: HashMap<String, String>,
+}
+
+/// Loads `.lang.ron` string tables.
+#[derive(Default)]
+pub struct StringTableLoader;
+
+impl AssetLoader for StringTableLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let table: StringTable = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(table));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang.ron"]
+    }
+}
+
+/// A loaded language, by its code (e.g. "en").
+pub struct Language {
+    pub code: String,
+    pub table: StringTable,
+}
+
+/// The string tables registered with `BasicAssetPlugin::with_language`,
+/// and which one is in use. Text is looked up by key; a key missing from
+/// the current language falls back to the first registered language, and
+/// then to the key itself, so untranslated text still shows up.
+#[derive(Default)]
+pub struct Localization {
+    pub languages: Vec<Language>,
+    current: String,
+}
+
+impl Localization {
+    /// The code of the language in use.
+    pub fn language(&self) -> &str {
+        &self.current
+    }
+
+    /// Switch languages. The tables may still be loading, so any code is
+    /// accepted; unknown languages show the fallback language.
+    pub fn set_language(&mut self, code: &str) {
+        self.current = code.to_string();
+    }
+
+    /// The display name of a language, or its code if it isn't loaded.
+    pub fn language_name<'a>(&'a self, code: &'a str) -> &'a str {
+        self.languages
+            .iter()
+            .find(|language| language.code == code)
+            .map_or(code, |language| language.table.name.as_str())
+    }
+
+    pub fn text(&self, key: &str) -> String {
+        self.languages
+            .iter()
+            .find(|language| language.code == self.current)
+            .and_then(|language| language.table.strings.get(key))
+            .or_else(|| {
+                self.languages
+                    .first()
+                    .and_then(|language| language.table.strings.get(key))
+            })
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Look up `key`, and replace each `{name}` in it with its value.
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> String {
+        args.iter().fold(self.text(key), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+    }
+}
+
+/// A loaded font, kept with its glyph data so we can tell which
+/// characters it can draw.
+pub struct LoadedFont {
+    pub handle: Handle<Font>,
+    glyphs: FontArc,
+}
+
+impl LoadedFont {
+    pub fn new(handle: Handle<Font>, font: &Font) -> Self {
+        Self {
+            handle,
+            glyphs: font.font.clone(),
+        }
+    }
+
+    /// Does the font have a glyph for every visible character of `text`?
+    pub fn covers(&self, text: &str) -> bool {
+        font_covers(&self.glyphs, text)
+    }
+}
+
+/// Glyph 0 is the "missing character" box.
+pub fn font_covers(font: &FontArc, text: &str) -> bool {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| font.glyph_id(c).0 != 0)
+}
@@ -1,37 +1,86 @@
-/// The asset manager is a reusable helper I sometimes use to make
-/// Bevy games quickly. It lets me specify my assets up-front,
-/// provides a Res<Assets> that gives me numbered access to asset
-/// handles (rather than passing around tons of handle-storing resources)
-/// and integrates with the loader to avoid asset popping.
+//! The asset manager is a reusable helper I sometimes use to make
+//! Bevy games quickly. It lets me specify my assets up-front,
+//! provides a Res<Assets> that gives me numbered access to asset
+//! handles (rather than passing around tons of handle-storing resources)
+//! and integrates with the loader to avoid asset popping.
 
 mod asset_management;
 pub use asset_management::*;
+mod atlas_manifest;
+pub use atlas_manifest::*;
+#[cfg(feature = "embedded_assets")]
+mod embedded;
+#[cfg(feature = "embedded_assets")]
+pub use embedded::*;
+mod hot_reload;
+use hot_reload::*;
 mod loader;
-use crate::GameMode;
-use bevy::prelude::*;
+mod localization;
+pub use localization::*;
+mod packed_atlas;
+pub use packed_atlas::*;
+mod screens;
+use crate::{AnimationClip, AnimationMode, GameMode};
+use bevy::{prelude::*, utils::HashMap};
 pub use loader::*;
+pub use screens::*;
 
 #[derive(Clone)]
-struct AtlasInfo {
-    filename: String,
-    sprite_width: f32,
-    sprite_height: f32,
-    columns: usize,
-    rows: usize,
+enum AtlasInfo {
+    Grid {
+        filename: String,
+        sprite_width: f32,
+        sprite_height: f32,
+        columns: usize,
+        rows: usize,
+    },
+    Packed {
+        manifest: String,
+    },
+}
+
+/// A clip whose frames are sprite names, resolved once atlases have loaded.
+#[derive(Clone)]
+struct SpriteClipInfo {
+    frames: Vec<String>,
+    frame_duration: f32,
+    mode: AnimationMode,
 }
 
 #[derive(Clone)]
 pub struct BasicAssetPlugin {
     atlases: Vec<AtlasInfo>,
+    clips: HashMap<String, AnimationClip>,
+    sprite_clips: HashMap<String, SpriteClipInfo>,
+    screens: HashMap<String, ScreenBackground>,
+    languages: Vec<(String, String)>,
+    fallback_fonts: Vec<String>,
+    hot_reload: bool,
 }
 
 impl Plugin for BasicAssetPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(self.clone())
+            .add_asset::<AtlasManifest>()
+            .init_asset_loader::<AtlasManifestLoader>()
+            .add_asset::<StringTable>()
+            .init_asset_loader::<StringTableLoader>()
             // Loading System Handler
             .add_system_set(SystemSet::on_enter(GameMode::Loading).with_system(setup_loading))
             .add_system_set(SystemSet::on_update(GameMode::Loading).with_system(run_loading))
             .add_system_set(SystemSet::on_exit(GameMode::Loading).with_system(exit_loading));
+
+        if self.hot_reload {
+            app.add_startup_system(enable_hot_reload)
+                .add_system(rebuild_modified_atlases)
+                .add_system(rebuild_modified_packed_atlases);
+        }
+    }
+}
+
+impl Default for BasicAssetPlugin {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -39,6 +88,12 @@ impl BasicAssetPlugin {
     pub fn new() -> Self {
         Self {
             atlases: Vec::new(),
+            clips: HashMap::default(),
+            sprite_clips: HashMap::default(),
+            screens: HashMap::default(),
+            languages: Vec::new(),
+            fallback_fonts: Vec::new(),
+            hot_reload: false,
         }
     }
 
@@ -50,7 +105,7 @@ impl BasicAssetPlugin {
         columns: usize,
         rows: usize,
     ) -> Self {
-        self.atlases.push(AtlasInfo {
+        self.atlases.push(AtlasInfo::Grid {
             filename: filename.to_string(),
             sprite_width,
             sprite_height,
@@ -59,4 +114,84 @@ impl BasicAssetPlugin {
         });
         self
     }
+
+    /// Add an atlas packed by the `pack_atlas` tool, from its `.atlas.ron`
+    /// manifest. Its sprites can be found by name with
+    /// `AssetManager::sprite_index`.
+    pub fn with_packed_atlas<S: ToString>(mut self, manifest: S) -> Self {
+        self.atlases.push(AtlasInfo::Packed {
+            manifest: manifest.to_string(),
+        });
+        self
+    }
+
+    /// Register a named animation clip. Frame indices refer to the atlas
+    /// of whichever sprite plays the clip.
+    pub fn with_clip<S: ToString>(
+        mut self,
+        name: S,
+        frames: &[usize],
+        frame_duration_ms: f32,
+        mode: AnimationMode,
+    ) -> Self {
+        self.clips.insert(
+            name.to_string(),
+            AnimationClip {
+                frames: frames.to_vec(),
+                frame_duration: frame_duration_ms,
+                mode,
+            },
+        );
+        self
+    }
+
+    /// Register a named animation clip whose frames are named sprites from
+    /// a packed atlas.
+    pub fn with_sprite_clip<S: ToString>(
+        mut self,
+        name: S,
+        frames: &[&str],
+        frame_duration_ms: f32,
+        mode: AnimationMode,
+    ) -> Self {
+        self.sprite_clips.insert(
+            name.to_string(),
+            SpriteClipInfo {
+                frames: frames.iter().map(|frame| frame.to_string()).collect(),
+                frame_duration: frame_duration_ms,
+                mode,
+            },
+        );
+        self
+    }
+
+    /// Register a named screen background (e.g. "main_menu"), for the menu
+    /// framework to display with `spawn_screen`.
+    pub fn with_screen<S: ToString>(mut self, name: S, background: ScreenBackground) -> Self {
+        self.screens.insert(name.to_string(), background);
+        self
+    }
+
+    /// Register a language's string table (a `.lang.ron` file) under its
+    /// code, e.g. "en". The first language registered is the fallback for
+    /// strings other languages haven't translated.
+    pub fn with_language<S: ToString>(mut self, code: S, filename: S) -> Self {
+        self.languages
+            .push((code.to_string(), filename.to_string()));
+        self
+    }
+
+    /// Add a font to draw text the default font has no glyphs for, such as
+    /// another script. Fallbacks are tried in the order they're added.
+    pub fn with_fallback_font<S: ToString>(mut self, filename: S) -> Self {
+        self.fallback_fonts.push(filename.to_string());
+        self
+    }
+
+    /// Development mode: watch the asset folder and reload images and fonts
+    /// when they change on disk, rebuilding the texture atlases that use them.
+    pub fn with_hot_reload(mut self, enabled: bool) -> Self {
+        self.hot_reload = enabled;
+        self
+    }
 }
@@ -0,0 +1,79 @@
+use super::AtlasManifest;
+use crate::AssetManager;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    sprite::Rect,
+    utils::BoxedFuture,
+};
+use std::path::Path;
+
+/// A texture atlas built from a packed sprite sheet manifest.
+pub struct PackedAtlas {
+    pub manifest: Handle<AtlasManifest>,
+    pub manifest_path: String,
+    pub atlas: Handle<TextureAtlas>,
+    /// The sprite names from the manifest, as last registered.
+    pub sprite_names: Vec<String>,
+}
+
+/// Loads `.atlas.ron` manifests written by the `pack_atlas` tool.
+#[derive(Default)]
+pub struct AtlasManifestLoader;
+
+impl AssetLoader for AtlasManifestLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let manifest: AtlasManifest = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(manifest));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["atlas.ron"]
+    }
+}
+
+/// The path of a manifest's image; the manifest names it relative to itself.
+pub fn manifest_texture_path(manifest_path: &str, manifest: &AtlasManifest) -> String {
+    Path::new(manifest_path)
+        .with_file_name(&manifest.texture)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Build a `TextureAtlas` from a manifest, in manifest order.
+pub fn build_packed_atlas(texture: Handle<Image>, manifest: &AtlasManifest) -> TextureAtlas {
+    let mut atlas = TextureAtlas::new_empty(
+        texture,
+        Vec2::new(manifest.width as f32, manifest.height as f32),
+    );
+    for sprite in manifest.sprites.iter() {
+        let min = Vec2::new(sprite.x as f32, sprite.y as f32);
+        let max = min + Vec2::new(sprite.width as f32, sprite.height as f32);
+        atlas.add_texture(Rect { min, max });
+    }
+    atlas
+}
+
+/// Record the name of every sprite in a packed atlas's manifest, so it can
+/// be found with `AssetManager::sprite_index`. Names the atlas registered
+/// before, from an older manifest, are forgotten first.
+pub fn register_sprite_names(assets: &mut AssetManager, atlas: usize, manifest: &AtlasManifest) {
+    for name in std::mem::take(&mut assets.packed_atlases[atlas].sprite_names) {
+        assets.sprites.remove(&name);
+    }
+    for (index, sprite) in manifest.sprites.iter().enumerate() {
+        assets.sprites.insert(sprite.name.clone(), index);
+    }
+    assets.packed_atlases[atlas].sprite_names = manifest
+        .sprites
+        .iter()
+        .map(|sprite| sprite.name.clone())
+        .collect();
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+
+/// A line of text drawn as part of a screen background.
+#[derive(Clone)]
+pub struct ScreenText {
+    pub text: String,
+    pub font_size: f32,
+    pub color: Color,
+    pub position: Vec3,
+}
+
+#[derive(Clone)]
+enum BackgroundSource {
+    Image {
+        filename: String,
+        width: f32,
+        height: f32,
+        columns: usize,
+        rows: usize,
+        index: usize,
+    },
+    Solid {
+        color: Color,
+        width: f32,
+        height: f32,
+    },
+}
+
+/// Describes a screen background to register with `BasicAssetPlugin::with_screen`:
+/// an image (or one cell of a grid image) or a solid colour, plus any
+/// text to draw over it.
+#[derive(Clone)]
+pub struct ScreenBackground {
+    source: BackgroundSource,
+    text: Vec<ScreenText>,
+}
+
+impl ScreenBackground {
+    /// A whole image, displayed at the given size.
+    pub fn image<S: ToString>(filename: S, width: f32, height: f32) -> Self {
+        Self::image_cell(filename, width, height, 1, 1, 0)
+    }
+
+    /// One cell of an image divided into a `columns` x `rows` grid of
+    /// `width` x `height` cells; `index` counts along the rows.
+    pub fn image_cell<S: ToString>(
+        filename: S,
+        width: f32,
+        height: f32,
+        columns: usize,
+        rows: usize,
+        index: usize,
+    ) -> Self {
+        Self {
+            source: BackgroundSource::Image {
+                filename: filename.to_string(),
+                width,
+                height,
+                columns,
+                rows,
+                index,
+            },
+            text: Vec::new(),
+        }
+    }
+
+    /// A solid rectangle of colour.
+    pub fn solid(color: Color, width: f32, height: f32) -> Self {
+        Self {
+            source: BackgroundSource::Solid {
+                color,
+                width,
+                height,
+            },
+            text: Vec::new(),
+        }
+    }
+
+    /// Add a line of text, centered on `position`. `text` is a string key,
+    /// translated when the screen is shown.
+    pub fn with_text<S: ToString>(
+        mut self,
+        text: S,
+        font_size: f32,
+        color: Color,
+        position: Vec3,
+    ) -> Self {
+        self.text.push(ScreenText {
+            text: text.to_string(),
+            font_size,
+            color,
+            position,
+        });
+        self
+    }
+
+    /// Queue any image this background needs, and turn it into a `ScreenAsset`.
+    pub(crate) fn load(
+        &self,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+        remaining_assets: &mut Vec<HandleUntyped>,
+    ) -> ScreenAsset {
+        let fill = match &self.source {
+            BackgroundSource::Image {
+                filename,
+                width,
+                height,
+                columns,
+                rows,
+                index,
+            } => {
+                let texture_handle: Handle<Image> = asset_server.load(filename.as_str());
+                remaining_assets.push(texture_handle.clone_untyped());
+                let atlas = TextureAtlas::from_grid(
+                    texture_handle,
+                    Vec2::new(*width, *height),
+                    *columns,
+                    *rows,
+                );
+                ScreenFill::Atlas {
+                    atlas: texture_atlases.add(atlas),
+                    index: *index,
+                }
+            }
+            BackgroundSource::Solid {
+                color,
+                width,
+                height,
+            } => ScreenFill::Solid {
+                color: *color,
+                size: Vec2::new(*width, *height),
+            },
+        };
+        ScreenAsset {
+            fill,
+            text: self.text.clone(),
+        }
+    }
+}
+
+/// How a loaded screen background is drawn.
+pub enum ScreenFill {
+    Atlas {
+        atlas: Handle<TextureAtlas>,
+        index: usize,
+    },
+    Solid {
+        color: Color,
+        size: Vec2,
+    },
+}
+
+/// A loaded screen background, stored by name in the `AssetManager`.
+pub struct ScreenAsset {
+    pub fill: ScreenFill,
+    pub text: Vec<ScreenText>,
+}
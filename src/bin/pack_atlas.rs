@@ -0,0 +1,196 @@
+//! Packs a directory of individual PNG sprites into a single sprite sheet,
+//! plus an `.atlas.ron` manifest of named rectangles for the asset manager.
+//!
+//! Usage: `cargo run --bin pack_atlas -- <sprite dir> <output prefix> [max width]`
+//!
+//! For example, `cargo run --bin pack_atlas -- art/sprites assets/spritesheet`
+//! writes `assets/spritesheet.png` and `assets/spritesheet.atlas.ron`. Each
+//! sprite is named after its file, without the extension.
+
+#[path = "../asset_manager/atlas_manifest.rs"]
+mod atlas_manifest;
+use atlas_manifest::{AtlasManifest, AtlasSprite};
+use image::{GenericImage, RgbaImage};
+use std::path::{Path, PathBuf};
+
+/// Transparent pixels left between sprites, so filtering can't bleed
+/// neighbours into each other.
+const PADDING: u32 = 1;
+
+const DEFAULT_MAX_WIDTH: u32 = 512;
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        eprintln!("Usage: pack_atlas <sprite dir> <output prefix> [max width]");
+        std::process::exit(1);
+    }
+    let input = Path::new(&args[1]);
+    let output = PathBuf::from(&args[2]);
+    let max_width = match args.get(3) {
+        Some(width) => width.parse()?,
+        None => DEFAULT_MAX_WIDTH,
+    };
+
+    let sprites = load_sprites(input)?;
+    if sprites.is_empty() {
+        anyhow::bail!("No PNG files found in {}", input.display());
+    }
+    let (placements, width, height) = pack(&sprites, max_width);
+
+    let mut sheet = RgbaImage::new(width, height);
+    for ((_, image), placed) in sprites.iter().zip(placements.iter()) {
+        sheet.copy_from(image, placed.x, placed.y)?;
+    }
+
+    let texture = output.with_extension("png");
+    let manifest_path = output.with_extension("atlas.ron");
+    sheet.save(&texture)?;
+
+    let manifest = AtlasManifest {
+        texture: texture
+            .file_name()
+            .expect("Output must name a file")
+            .to_string_lossy()
+            .to_string(),
+        width,
+        height,
+        sprites: placements,
+    };
+    let pretty = ron::ser::PrettyConfig::new();
+    std::fs::write(
+        &manifest_path,
+        ron::ser::to_string_pretty(&manifest, pretty)? + "\n",
+    )?;
+
+    println!(
+        "Packed {} sprites into {} ({}x{}) and {}",
+        manifest.sprites.len(),
+        texture.display(),
+        width,
+        height,
+        manifest_path.display()
+    );
+    Ok(())
+}
+
+/// Every PNG in the directory, sorted by name so output is repeatable.
+fn load_sprites(dir: &Path) -> anyhow::Result<Vec<(String, RgbaImage)>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "png").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    let mut sprites = Vec::new();
+    for path in paths {
+        let name = path
+            .file_stem()
+            .expect("PNG files have a name")
+            .to_string_lossy()
+            .to_string();
+        let image = image::open(&path)?.to_rgba8();
+        sprites.push((name, image));
+    }
+    Ok(sprites)
+}
+
+/// Shelf packing: sprites are placed left to right in rows no wider than
+/// `max_width`, tallest first. Returns each sprite's rectangle (in input
+/// order) and the sheet size.
+fn pack(sprites: &[(String, RgbaImage)], max_width: u32) -> (Vec<AtlasSprite>, u32, u32) {
+    let mut order: Vec<usize> = (0..sprites.len()).collect();
+    order.sort_by_key(|i| std::cmp::Reverse(sprites[*i].1.height()));
+
+    let mut placements: Vec<Option<AtlasSprite>> = vec![None; sprites.len()];
+    let (mut x, mut y, mut row_height, mut width) = (0, 0, 0, 0);
+    for i in order {
+        let (name, image) = &sprites[i];
+        if x > 0 && x + image.width() > max_width {
+            x = 0;
+            y += row_height + PADDING;
+            row_height = 0;
+        }
+        placements[i] = Some(AtlasSprite {
+            name: name.clone(),
+            x,
+            y,
+            width: image.width(),
+            height: image.height(),
+        });
+        x += image.width() + PADDING;
+        width = width.max(x - PADDING);
+        row_height = row_height.max(image.height());
+    }
+
+    let placements = placements
+        .into_iter()
+        .map(|placed| placed.expect("Every sprite is placed"))
+        .collect();
+    (placements, width, y + row_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprites(sizes: &[(u32, u32)]) -> Vec<(String, RgbaImage)> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, (width, height))| (format!("sprite{}", i), RgbaImage::new(*width, *height)))
+            .collect()
+    }
+
+    fn overlaps(a: &AtlasSprite, b: &AtlasSprite) -> bool {
+        a.x < b.x + b.width + PADDING
+            && b.x < a.x + a.width + PADDING
+            && a.y < b.y + b.height + PADDING
+            && b.y < a.y + a.height + PADDING
+    }
+
+    #[test]
+    fn sprites_keep_their_padding_apart() {
+        let sprites = sprites(&[(30, 10), (20, 40), (50, 25), (10, 10), (64, 8), (33, 33)]);
+        let (placements, _, _) = pack(&sprites, 100);
+        for (i, a) in placements.iter().enumerate() {
+            for b in placements.iter().skip(i + 1) {
+                assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn sprites_stay_inside_the_sheet() {
+        let sprites = sprites(&[(30, 10), (20, 40), (50, 25), (10, 10), (64, 8), (33, 33)]);
+        let (placements, width, height) = pack(&sprites, 100);
+        assert!(width <= 100);
+        for placed in placements.iter() {
+            assert!(placed.x + placed.width <= width);
+            assert!(placed.y + placed.height <= height);
+        }
+    }
+
+    #[test]
+    fn placements_are_in_input_order() {
+        let sprites = sprites(&[(8, 8), (8, 16), (8, 4)]);
+        let (placements, _, _) = pack(&sprites, 100);
+        let names: Vec<&str> = placements.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["sprite0", "sprite1", "sprite2"]);
+        assert_eq!((placements[1].width, placements[1].height), (8, 16));
+    }
+
+    #[test]
+    fn a_sprite_wider_than_the_limit_gets_its_own_row() {
+        let sprites = sprites(&[(20, 20), (150, 10), (20, 20)]);
+        let (placements, width, height) = pack(&sprites, 100);
+        // The sheet grows to fit it, rather than failing or clipping it.
+        assert_eq!(width, 150);
+        let wide = &placements[1];
+        assert_eq!(wide.x, 0);
+        for other in [&placements[0], &placements[2]] {
+            assert!(other.y + other.height + PADDING <= wide.y || other.y >= wide.y + wide.height);
+        }
+        assert!(wide.y + wide.height <= height);
+    }
+}
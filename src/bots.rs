@@ -0,0 +1,309 @@
+//! Computer-controlled players. A bot flies its ship through the same
+//! `ActionInput` as a human, so it can only do what a player can: turn,
+//! thrust, or coast. Every so often (more often on harder difficulties) it
+//! looks over the playfield and picks somewhere to go; in between, it
+//! steers for that spot.
+//!
+//! A bot's first concern is the black hole: it plots where it would drift
+//! to, and climbs away if that passes too close. After that it goes for
+//! the nearest salvage it can safely reach, unless an opponent is closer
+//! and lined up to be knocked into the well. It fires at any opponent it
+//! happens to be facing. When thrust burns fuel, a bot running low saves
+//! what it has left for climbing away from the well, and never burns its
+//! tank dry.
+
+use crate::{
+    components::{Player, Salvage},
+    ActionInput, Attractor, Fuel, InputAction, Invulnerable, MatchRules, RandomNumbers, Velocity,
+    ARENA_HEIGHT, ARENA_WIDTH,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Plotted courses look this many physics ticks (of 33ms) ahead.
+const LOOKAHEAD_TICKS: usize = 45;
+/// The closest even the most reckless bot will plan to pass the well.
+const MIN_CLEARANCE: f32 = 50.0;
+/// Extra clearance kept by a bot that takes no risks at all.
+const CAUTION: f32 = 120.0;
+/// How fast a bot tries to travel. Ships top out at 5.
+const CRUISE_SPEED: f32 = 4.0;
+/// Opponents further away than this aren't worth chasing.
+const RAM_RANGE: f32 = 250.0;
+/// Turn until facing within this many degrees of the way to go.
+const TURN_TOLERANCE: f32 = 5.0;
+/// Only thrust when facing within this many degrees of the way to go.
+const THRUST_CONE: f32 = 35.0;
+/// Fire at opponents this close, within this many degrees of straight ahead.
+const FIRE_RANGE: f32 = 300.0;
+const FIRE_CONE: f32 = 8.0;
+/// Below this fuel level, a bot only thrusts to escape the well.
+const LOW_FUEL: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BotDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl BotDifficulty {
+    pub const ALL: [BotDifficulty; 3] = [
+        BotDifficulty::Easy,
+        BotDifficulty::Normal,
+        BotDifficulty::Hard,
+    ];
+
+    /// The string key of the difficulty's name.
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            BotDifficulty::Easy => "control.bot_easy",
+            BotDifficulty::Normal => "control.bot_normal",
+            BotDifficulty::Hard => "control.bot_hard",
+        }
+    }
+
+    /// Seconds between looking over the playfield.
+    fn reaction_time(&self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.5,
+            BotDifficulty::Normal => 0.25,
+            BotDifficulty::Hard => 0.1,
+        }
+    }
+
+    /// The most degrees the bot's aim can be off by.
+    fn aim_noise(&self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 20.0,
+            BotDifficulty::Normal => 10.0,
+            BotDifficulty::Hard => 3.0,
+        }
+    }
+
+    /// From 0 to 1: how close to the well the bot will fly, and how far
+    /// from it an opponent can be and still be worth ramming.
+    fn risk_tolerance(&self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.2,
+            BotDifficulty::Normal => 0.5,
+            BotDifficulty::Hard => 0.8,
+        }
+    }
+}
+
+/// Flies a player's ship. Added when the ship spawns, if the player's slot
+/// is set to a bot.
+#[derive(Component)]
+pub struct Bot {
+    difficulty: BotDifficulty,
+    timer: Timer,
+    /// Where the bot is heading, until it next looks around.
+    target: Option<Vec2>,
+    /// Whether it's heading there to get away from a well.
+    escaping: bool,
+}
+
+impl Bot {
+    pub fn new(difficulty: BotDifficulty) -> Self {
+        Self {
+            difficulty,
+            timer: Timer::from_seconds(difficulty.reaction_time(), true),
+            target: None,
+            escaping: false,
+        }
+    }
+
+    /// The closest to a well the bot plans to fly.
+    fn clearance(&self) -> f32 {
+        MIN_CLEARANCE + CAUTION * (1.0 - self.difficulty.risk_tolerance())
+    }
+}
+
+/// Something moving on the playfield.
+#[derive(Clone, Copy)]
+struct Body {
+    position: Vec2,
+    velocity: Vec2,
+}
+
+/// The closest a coasting body comes to any of the wells in the next
+/// `LOOKAHEAD_TICKS`, following the same pull as `velocity_attractor_2d`.
+fn closest_approach(body: Body, wells: &[Vec2]) -> f32 {
+    let Body {
+        mut position,
+        mut velocity,
+    } = body;
+    let mut closest = f32::MAX;
+    for _ in 0..LOOKAHEAD_TICKS {
+        for well in wells {
+            let distance_squared = well.distance_squared(position);
+            if distance_squared > 0.0 {
+                velocity += (*well - position).normalize() / distance_squared * 2000.0;
+            }
+            closest = closest.min(well.distance(position));
+        }
+        position += velocity;
+    }
+    closest
+}
+
+fn nearest_well(point: Vec2, wells: &[Vec2]) -> Option<Vec2> {
+    wells
+        .iter()
+        .copied()
+        .min_by(|a, b| a.distance(point).total_cmp(&b.distance(point)))
+}
+
+/// Pick somewhere to go: away from danger, into a rival, or to salvage.
+/// Also says whether it's an escape from a well.
+fn plan(bot: &Bot, me: Body, wells: &[Vec2], salvage: &[Body], rivals: &[Body]) -> (Vec2, bool) {
+    let clearance = bot.clearance();
+
+    // Drifting into a well: climb out, along the orbit rather than
+    // straight up, so the thrust isn't spent fighting the current speed.
+    if closest_approach(me, wells) < clearance {
+        if let Some(well) = nearest_well(me.position, wells) {
+            let out = (me.position - well).normalize_or_zero();
+            let mut along = out.perp();
+            if along.dot(me.velocity) < 0.0 {
+                along = -along;
+            }
+            return (
+                me.position + (out + along).normalize_or_zero() * 200.0,
+                true,
+            );
+        }
+    }
+
+    // Rivals close to a well, with the bot behind them, can be pushed in.
+    let ram_depth = 100.0 + 200.0 * bot.difficulty.risk_tolerance();
+    let ram = rivals
+        .iter()
+        .filter(|rival| {
+            let to_rival = rival.position - me.position;
+            let well = if let Some(well) = nearest_well(rival.position, wells) {
+                well
+            } else {
+                return false;
+            };
+            to_rival.length() < RAM_RANGE
+                && rival.position.distance(well) < ram_depth
+                && me.position.distance(well) > clearance
+                && to_rival
+                    .normalize_or_zero()
+                    .dot((well - rival.position).normalize_or_zero())
+                    > 0.8
+        })
+        .map(|rival| (rival.position, rival.position.distance(me.position)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    // Lead moving salvage, and leave any that will be too near a well.
+    let pickup = salvage
+        .iter()
+        .map(|piece| {
+            let travel = piece.position.distance(me.position) / CRUISE_SPEED;
+            piece.position + piece.velocity * travel
+        })
+        .filter(|meet| {
+            wells.iter().all(|well| well.distance(*meet) > clearance)
+                && meet.x.abs() < ARENA_WIDTH / 2.0
+                && meet.y.abs() < ARENA_HEIGHT / 2.0
+        })
+        .map(|meet| (meet, meet.distance(me.position)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    let target = match (ram, pickup) {
+        (Some((rival, ram_distance)), Some((_, pickup_distance)))
+            if ram_distance < pickup_distance =>
+        {
+            rival
+        }
+        (Some((rival, _)), None) => rival,
+        (_, Some((meet, _))) => meet,
+        // Nothing to do: keep drifting.
+        (None, None) => me.position + me.velocity * 10.0,
+    };
+    (target, false)
+}
+
+/// Look around when it's time, then turn towards the target and thrust
+/// when facing it. Bots steer for the change in velocity they need, not
+/// straight at the target, so they allow for the orbit they're already in.
+#[allow(clippy::too_many_arguments)]
+pub fn drive_bots(
+    time: Res<Time>,
+    rng: Res<RandomNumbers>,
+    rules: Res<MatchRules>,
+    mut actions: ResMut<ActionInput>,
+    mut bots: Query<(&mut Bot, &Player, &Transform, &Velocity, &Fuel)>,
+    ships: Query<(&Player, &Transform, &Velocity), Without<Invulnerable>>,
+    salvage: Query<(&Transform, &Velocity), With<Salvage>>,
+    attractors: Query<&Transform, With<Attractor>>,
+) {
+    let body = |transform: &Transform, velocity: &Velocity| Body {
+        position: transform.translation.truncate(),
+        velocity: velocity.0.truncate(),
+    };
+    let wells: Vec<Vec2> = attractors
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let salvage: Vec<Body> = salvage
+        .iter()
+        .map(|(transform, velocity)| body(transform, velocity))
+        .collect();
+
+    for (mut bot, player, transform, velocity, fuel) in bots.iter_mut() {
+        let me = body(transform, velocity);
+        bot.timer.tick(time.delta());
+        if bot.target.is_none() || bot.timer.just_finished() {
+            let rivals: Vec<Body> = ships
+                .iter()
+                .filter(|(other, _, _)| other.id != player.id)
+                .map(|(_, transform, velocity)| body(transform, velocity))
+                .collect();
+            let (target, escaping) = plan(&bot, me, &wells, &salvage, &rivals);
+            bot.escaping = escaping;
+            let noise = bot.difficulty.aim_noise() * (rng.range(0, 2001) as f32 / 1000.0 - 1.0);
+            let aim =
+                Quat::from_rotation_z(noise.to_radians()) * (target - me.position).extend(0.0);
+            bot.target = Some(me.position + aim.truncate());
+        }
+
+        let target = bot.target.unwrap_or(me.position);
+        let wanted = (target - me.position).normalize_or_zero() * CRUISE_SPEED;
+        let steer = wanted - me.velocity;
+        let facing = transform.local_y().truncate();
+        let (turn, mut thrust) = if steer.length() > 0.5 {
+            let angle = facing.perp_dot(steer).atan2(facing.dot(steer)).to_degrees();
+            (angle, angle.abs() < THRUST_CONE)
+        } else {
+            (0.0, false)
+        };
+        // Keep low fuel for escapes, and never run the tank dry.
+        if rules.fuel && (fuel.in_reserve() || (fuel.level() < LOW_FUEL && !bot.escaping)) {
+            thrust = false;
+        }
+        let pressed = |on: bool| if on { 1.0 } else { 0.0 };
+        actions.drive(
+            player.id,
+            InputAction::RotateLeft,
+            pressed(turn > TURN_TOLERANCE),
+        );
+        actions.drive(
+            player.id,
+            InputAction::RotateRight,
+            pressed(turn < -TURN_TOLERANCE),
+        );
+        actions.drive(player.id, InputAction::Thrust, pressed(thrust));
+
+        let in_sights = ships.iter().any(|(other, transform, _)| {
+            let to_other = transform.translation.truncate() - me.position;
+            other.id != player.id
+                && to_other.length() < FIRE_RANGE
+                && facing.angle_between(to_other).to_degrees().abs() < FIRE_CONE
+        });
+        actions.drive(player.id, InputAction::Fire, pressed(in_sights));
+    }
+}
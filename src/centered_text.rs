@@ -1,3 +1,7 @@
+// The Bevy 0.7 `Bundle` derive calls `mem::forget` on every field, and the
+// lint fires inside the generated impl rather than on the struct.
+#![allow(clippy::forget_non_drop)]
+
 use bevy::{prelude::*, text::Text2dSize};
 
 pub fn centered_text_bundle<S: ToString>(
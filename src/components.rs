@@ -1,8 +1,6 @@
+use crate::SalvageKind;
 use bevy::prelude::*;
 
-#[derive(Component)]
-pub struct PlayGameElement;
-
 #[derive(Component)]
 pub struct Player {
     pub id: usize,
@@ -12,4 +10,6 @@ pub struct Player {
 pub struct EmitTrail;
 
 #[derive(Component)]
-pub struct Salvage;
+pub struct Salvage {
+    pub kind: SalvageKind,
+}
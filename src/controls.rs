@@ -0,0 +1,586 @@
+//! An input-action layer. Game code asks whether a player is pressing
+//! `Thrust`, rather than which key that is; each player has a map from
+//! actions to keyboard keys, gamepad buttons and gamepad axes, which is
+//! saved with the rest of the `Settings`.
+//!
+//! Gamepads join by pressing Start, which gives the pad to the first
+//! player who doesn't have one. A pad that disconnects keeps its player,
+//! so plugging it back in carries on where it left off.
+
+use crate::Settings;
+use bevy::{
+    input::{gamepad::GamepadEventType, InputSystem},
+    prelude::*,
+    utils::HashSet,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Labels the system that updates `ActionInput`, in `CoreStage::PreUpdate`.
+#[derive(SystemLabel, Clone, Hash, Debug, PartialEq, Eq)]
+pub struct ActionInputSystem;
+
+/// How far an axis (or analog trigger) must move to count as "pressed".
+const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InputAction {
+    RotateLeft,
+    RotateRight,
+    Thrust,
+    Fire,
+    Confirm,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 5] = [
+        InputAction::RotateLeft,
+        InputAction::RotateRight,
+        InputAction::Thrust,
+        InputAction::Fire,
+        InputAction::Confirm,
+    ];
+
+    /// The string key of the action's name.
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            InputAction::RotateLeft => "action.rotate_left",
+            InputAction::RotateRight => "action.rotate_right",
+            InputAction::Thrust => "action.thrust",
+            InputAction::Fire => "action.fire",
+            InputAction::Confirm => "action.confirm",
+        }
+    }
+}
+
+/// Something a player can press to trigger an action.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButtonType),
+    /// A gamepad axis, pushed in the positive or negative direction.
+    Axis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+impl Binding {
+    /// Keyboard bindings are shared by everyone, so they conflict across
+    /// players; gamepad bindings only conflict with the same player's.
+    fn is_keyboard(&self) -> bool {
+        matches!(self, Binding::Key(_))
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Button(button) => write!(f, "Pad {:?}", button),
+            Binding::Axis { axis, positive } => {
+                write!(f, "Pad {:?}{}", axis, if *positive { "+" } else { "-" })
+            }
+        }
+    }
+}
+
+/// One player's controls.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerBindings {
+    pub actions: BTreeMap<InputAction, Vec<Binding>>,
+}
+
+impl PlayerBindings {
+    /// The out-of-the-box controls: arrows for player 1, WASD for player
+    /// 2, IJKL for player 3 and the numeric keypad for player 4, and the
+    /// same gamepad layout for everyone.
+    pub fn default_for(player: usize) -> Self {
+        let (left, right, thrust, fire, confirm) = match player {
+            0 => (
+                KeyCode::Left,
+                KeyCode::Right,
+                KeyCode::Up,
+                KeyCode::Down,
+                KeyCode::Return,
+            ),
+            1 => (
+                KeyCode::A,
+                KeyCode::D,
+                KeyCode::W,
+                KeyCode::S,
+                KeyCode::Space,
+            ),
+            2 => (KeyCode::J, KeyCode::L, KeyCode::I, KeyCode::K, KeyCode::U),
+            _ => (
+                KeyCode::Numpad4,
+                KeyCode::Numpad6,
+                KeyCode::Numpad8,
+                KeyCode::Numpad5,
+                KeyCode::NumpadEnter,
+            ),
+        };
+        let mut actions = BTreeMap::new();
+        actions.insert(
+            InputAction::RotateLeft,
+            vec![
+                Binding::Key(left),
+                Binding::Button(GamepadButtonType::DPadLeft),
+                Binding::Axis {
+                    axis: GamepadAxisType::LeftStickX,
+                    positive: false,
+                },
+            ],
+        );
+        actions.insert(
+            InputAction::RotateRight,
+            vec![
+                Binding::Key(right),
+                Binding::Button(GamepadButtonType::DPadRight),
+                Binding::Axis {
+                    axis: GamepadAxisType::LeftStickX,
+                    positive: true,
+                },
+            ],
+        );
+        actions.insert(
+            InputAction::Thrust,
+            vec![
+                Binding::Key(thrust),
+                Binding::Button(GamepadButtonType::South),
+                Binding::Button(GamepadButtonType::RightTrigger2),
+            ],
+        );
+        actions.insert(
+            InputAction::Fire,
+            vec![
+                Binding::Key(fire),
+                Binding::Button(GamepadButtonType::West),
+                Binding::Button(GamepadButtonType::RightTrigger),
+            ],
+        );
+        actions.insert(
+            InputAction::Confirm,
+            vec![
+                Binding::Key(confirm),
+                Binding::Button(GamepadButtonType::South),
+            ],
+        );
+        Self { actions }
+    }
+
+    /// Give any action missing from the map its default bindings, so
+    /// actions added since the settings were saved work out of the box.
+    pub fn add_missing(&mut self, player: usize) {
+        for (action, bindings) in Self::default_for(player).actions {
+            self.actions.entry(action).or_insert(bindings);
+        }
+    }
+
+    pub fn bindings(&self, action: InputAction) -> &[Binding] {
+        self.actions
+            .get(&action)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Bind an action, replacing its existing bindings of the same kind (a
+    /// new key replaces the old key, but leaves the gamepad alone).
+    pub fn rebind(&mut self, action: InputAction, binding: Binding) {
+        let bindings = self.actions.entry(action).or_default();
+        bindings.retain(|b| std::mem::discriminant(b) != std::mem::discriminant(&binding));
+        bindings.push(binding);
+    }
+
+    /// A readable summary of an action's keys and buttons, for menus.
+    /// Empty if there are none.
+    pub fn describe(&self, action: InputAction) -> String {
+        let bindings: Vec<String> = self
+            .bindings(action)
+            .iter()
+            .filter(|b| !matches!(b, Binding::Axis { .. }))
+            .map(|b| b.to_string())
+            .collect();
+        bindings.join(" / ")
+    }
+}
+
+/// Find another in-game action that already uses `binding`. `Confirm` is
+/// only read by menus, where any player may use it, so it never conflicts.
+pub fn find_conflict<'a>(
+    players: impl IntoIterator<Item = &'a PlayerBindings>,
+    player: usize,
+    action: InputAction,
+    binding: Binding,
+) -> Option<(usize, InputAction)> {
+    if action == InputAction::Confirm {
+        return None;
+    }
+    players
+        .into_iter()
+        .enumerate()
+        .find_map(|(other, bindings)| {
+            if other != player && !binding.is_keyboard() {
+                return None;
+            }
+            bindings.actions.iter().find_map(|(other_action, list)| {
+                let same = other == player && *other_action == action;
+                if !same && *other_action != InputAction::Confirm && list.contains(&binding) {
+                    Some((other, *other_action))
+                } else {
+                    None
+                }
+            })
+        })
+}
+
+#[derive(Default)]
+struct PlayerActionState {
+    values: BTreeMap<InputAction, f32>,
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    /// Held when `require_fresh_press` was called; ignored until released.
+    latched: HashSet<InputAction>,
+}
+
+/// Which gamepad, if any, each player is using.
+#[derive(Default)]
+pub struct GamepadAssignments {
+    players: Vec<Option<Gamepad>>,
+    just_joined: Vec<Gamepad>,
+}
+
+impl GamepadAssignments {
+    pub fn gamepad(&self, player: usize) -> Option<Gamepad> {
+        self.players.get(player).copied().flatten()
+    }
+
+    pub fn player(&self, gamepad: Gamepad) -> Option<usize> {
+        self.players.iter().position(|pad| *pad == Some(gamepad))
+    }
+
+    /// Did this gamepad join this frame? Its Start press shouldn't also
+    /// count as pausing the game.
+    pub fn just_joined(&self, gamepad: Gamepad) -> bool {
+        self.just_joined.contains(&gamepad)
+    }
+}
+
+/// Give a gamepad whose Start button was pressed to the first player
+/// without one, skipping bots.
+fn join_gamepads(
+    settings: Res<Settings>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mut assignments: ResMut<GamepadAssignments>,
+) {
+    assignments.just_joined.clear();
+    let player_count = settings.player_count.min(settings.players.len());
+    if assignments.players.len() < player_count {
+        assignments.players.resize(player_count, None);
+    }
+    for gamepad in gamepads.iter() {
+        if assignments.player(*gamepad).is_some()
+            || !buttons.just_pressed(GamepadButton(*gamepad, GamepadButtonType::Start))
+        {
+            continue;
+        }
+        if let Some(slot) = (0..player_count).find(|slot| {
+            assignments.players[*slot].is_none() && settings.players[*slot].bot.is_none()
+        }) {
+            assignments.players[slot] = Some(*gamepad);
+            assignments.just_joined.push(*gamepad);
+            info!("{:?} joined as player {}", gamepad, slot + 1);
+        }
+    }
+}
+
+fn log_gamepad_connections(
+    mut events: EventReader<GamepadEvent>,
+    assignments: Res<GamepadAssignments>,
+) {
+    for GamepadEvent(gamepad, event) in events.iter() {
+        if let Some(player) = assignments.player(*gamepad) {
+            match event {
+                GamepadEventType::Disconnected => {
+                    info!("Player {}'s gamepad disconnected", player + 1)
+                }
+                GamepadEventType::Connected => {
+                    info!("Player {}'s gamepad reconnected", player + 1)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// This frame's state of every player's actions. Every player reads the
+/// keyboard, plus their assigned gamepad if they have one.
+#[derive(Default)]
+pub struct ActionInput {
+    players: Vec<PlayerActionState>,
+    blocked: bool,
+}
+
+impl ActionInput {
+    pub fn pressed(&self, player: usize, action: InputAction) -> bool {
+        !self.blocked
+            && self
+                .players
+                .get(player)
+                .is_some_and(|state| state.pressed.contains(&action))
+    }
+
+    pub fn just_pressed(&self, player: usize, action: InputAction) -> bool {
+        !self.blocked
+            && self
+                .players
+                .get(player)
+                .is_some_and(|state| state.just_pressed.contains(&action))
+    }
+
+    /// How far the action is pressed, from 0 to 1. Keys and buttons are
+    /// either 0 or 1; axes and triggers can be anywhere in between.
+    pub fn value(&self, player: usize, action: InputAction) -> f32 {
+        if self.blocked {
+            return 0.0;
+        }
+        self.players
+            .get(player)
+            .and_then(|state| state.values.get(&action))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Set one of a player's actions directly, rather than from their
+    /// bindings. This is how bots fly; the value lasts until it's set again.
+    pub fn drive(&mut self, player: usize, action: InputAction, value: f32) {
+        if let Some(state) = self.players.get_mut(player) {
+            state.values.insert(action, value);
+            if value < AXIS_PRESS_THRESHOLD {
+                state.pressed.remove(&action);
+            } else if state.pressed.insert(action) {
+                state.just_pressed.insert(action);
+            }
+        }
+    }
+
+    /// While blocked, every action reads as released.
+    pub fn set_blocked(&mut self, blocked: bool) {
+        self.blocked = blocked;
+    }
+
+    /// Ignore every action that's currently held until it's released and
+    /// pressed again, so a press meant for one screen can't carry over to
+    /// the next.
+    pub fn require_fresh_press(&mut self) {
+        for state in self.players.iter_mut() {
+            let held = std::mem::take(&mut state.pressed);
+            state.just_pressed.clear();
+            for action in held.iter() {
+                state.values.insert(*action, 0.0);
+            }
+            state.latched.extend(held);
+        }
+    }
+
+    /// Did any player just press the action?
+    pub fn any_just_pressed(&self, action: InputAction) -> bool {
+        !self.blocked
+            && self
+                .players
+                .iter()
+                .any(|state| state.just_pressed.contains(&action))
+    }
+}
+
+/// The raw input devices, for reading bindings.
+struct RawInput<'a> {
+    keyboard: &'a Input<KeyCode>,
+    buttons: &'a Input<GamepadButton>,
+    button_axes: &'a Axis<GamepadButton>,
+    axes: &'a Axis<GamepadAxis>,
+    deadzone: f32,
+}
+
+impl RawInput<'_> {
+    fn value(&self, binding: &Binding, gamepad: Option<Gamepad>) -> f32 {
+        match (binding, gamepad) {
+            (Binding::Key(key), _) => self.keyboard.pressed(*key) as u8 as f32,
+            (Binding::Button(button), Some(gamepad)) => {
+                // Triggers are analog; other buttons read as 0 or 1.
+                let button = GamepadButton(gamepad, *button);
+                match self.button_axes.get(button) {
+                    Some(value) => self.past_deadzone(value),
+                    None => self.buttons.pressed(button) as u8 as f32,
+                }
+            }
+            (Binding::Axis { axis, positive }, Some(gamepad)) => {
+                let value = self.axes.get(GamepadAxis(gamepad, *axis)).unwrap_or(0.0);
+                self.past_deadzone(if *positive { value } else { -value })
+            }
+            (_, None) => 0.0,
+        }
+    }
+
+    /// Rescale an analog value past the deadzone, so steering and thrust
+    /// start smoothly from 0.
+    fn past_deadzone(&self, value: f32) -> f32 {
+        ((value - self.deadzone) / (1.0 - self.deadzone)).clamp(0.0, 1.0)
+    }
+}
+
+fn update_actions(
+    settings: Res<Settings>,
+    assignments: Res<GamepadAssignments>,
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    button_axes: Res<Axis<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut actions: ResMut<ActionInput>,
+) {
+    let input = RawInput {
+        keyboard: &keyboard,
+        buttons: &buttons,
+        button_axes: &button_axes,
+        axes: &axes,
+        deadzone: settings.gamepad_deadzone.clamp(0.0, 0.9),
+    };
+    // Every slot is read, not just the players in new matches, so
+    // changing the count mid-match doesn't leave anyone without controls.
+    actions
+        .players
+        .resize_with(settings.players.len(), Default::default);
+    for (id, (slot, state)) in settings
+        .players
+        .iter()
+        .zip(actions.players.iter_mut())
+        .enumerate()
+    {
+        state.just_pressed.clear();
+        // Bots don't read devices; their actions come from `drive`.
+        if slot.bot.is_some() {
+            continue;
+        }
+        let gamepad = assignments.gamepad(id);
+        let was_pressed = std::mem::take(&mut state.pressed);
+        for action in InputAction::ALL {
+            let mut value = slot
+                .bindings
+                .bindings(action)
+                .iter()
+                .map(|binding| input.value(binding, gamepad))
+                .fold(0.0, f32::max);
+            if state.latched.contains(&action) {
+                if value >= AXIS_PRESS_THRESHOLD {
+                    value = 0.0;
+                } else {
+                    state.latched.remove(&action);
+                }
+            }
+            state.values.insert(action, value);
+            if value >= AXIS_PRESS_THRESHOLD {
+                state.pressed.insert(action);
+                if !was_pressed.contains(&action) {
+                    state.just_pressed.insert(action);
+                }
+            }
+        }
+    }
+}
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionInput>()
+            .init_resource::<GamepadAssignments>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                join_gamepads.label("join_gamepads").after(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                update_actions
+                    .label(ActionInputSystem)
+                    .after("join_gamepads"),
+            )
+            .add_system(log_gamepad_connections);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players() -> Vec<PlayerBindings> {
+        (0..2).map(PlayerBindings::default_for).collect()
+    }
+
+    #[test]
+    fn a_key_another_player_uses_conflicts() {
+        let conflict = find_conflict(&players(), 0, InputAction::Thrust, Binding::Key(KeyCode::A));
+        assert_eq!(conflict, Some((1, InputAction::RotateLeft)));
+    }
+
+    #[test]
+    fn a_key_another_of_your_actions_uses_conflicts() {
+        let conflict = find_conflict(
+            &players(),
+            0,
+            InputAction::Thrust,
+            Binding::Key(KeyCode::Down),
+        );
+        assert_eq!(conflict, Some((0, InputAction::Fire)));
+    }
+
+    #[test]
+    fn rebinding_an_action_to_its_own_key_is_fine() {
+        let conflict = find_conflict(
+            &players(),
+            0,
+            InputAction::Thrust,
+            Binding::Key(KeyCode::Up),
+        );
+        assert_eq!(conflict, None);
+    }
+
+    #[test]
+    fn gamepad_buttons_only_conflict_for_the_same_player() {
+        let button = Binding::Button(GamepadButtonType::West);
+        assert_eq!(
+            find_conflict(&players(), 0, InputAction::Thrust, button),
+            Some((0, InputAction::Fire))
+        );
+        let mut players = players();
+        players[0].rebind(InputAction::Fire, Binding::Button(GamepadButtonType::North));
+        // Player 2 still fires with West, but on their own pad.
+        assert_eq!(
+            find_conflict(&players, 0, InputAction::Thrust, button),
+            None
+        );
+    }
+
+    #[test]
+    fn confirm_never_conflicts() {
+        assert_eq!(
+            find_conflict(
+                &players(),
+                0,
+                InputAction::Confirm,
+                Binding::Key(KeyCode::A)
+            ),
+            None
+        );
+        assert_eq!(
+            find_conflict(
+                &players(),
+                0,
+                InputAction::Thrust,
+                Binding::Key(KeyCode::Return)
+            ),
+            None
+        );
+    }
+}
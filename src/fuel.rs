@@ -0,0 +1,73 @@
+//! Thrust burns fuel, when the match rules say so. Fuel trickles back on
+//! its own, and collecting salvage tops it up. A ship that runs dry
+//! flames out: it can't thrust again until it has some fuel back, and
+//! until then it's at the mercy of the black hole.
+
+use bevy::prelude::*;
+
+const MAX_FUEL: f32 = 100.0;
+/// Fuel burned per second at full thrust.
+pub const BURN_RATE: f32 = 25.0;
+/// Fuel regained per second, whether or not the ship is thrusting.
+const REGEN_RATE: f32 = 6.0;
+/// A ship that has flamed out can thrust again at this much fuel.
+const RESTART_LEVEL: f32 = 20.0;
+/// Fuel gained by collecting a piece of salvage.
+pub const SALVAGE_REFILL: f32 = 20.0;
+
+#[derive(Component)]
+pub struct Fuel {
+    amount: f32,
+    flamed_out: bool,
+}
+
+impl Default for Fuel {
+    fn default() -> Self {
+        Self {
+            amount: MAX_FUEL,
+            flamed_out: false,
+        }
+    }
+}
+
+impl Fuel {
+    /// Fuel left, from 0 to 1.
+    pub fn level(&self) -> f32 {
+        self.amount / MAX_FUEL
+    }
+
+    /// Can the engine fire?
+    pub fn can_thrust(&self) -> bool {
+        !self.flamed_out
+    }
+
+    /// Is the tank down to the level a flamed-out engine restarts at? Bots
+    /// stop thrusting here, so they never flame out.
+    pub fn in_reserve(&self) -> bool {
+        self.amount <= RESTART_LEVEL
+    }
+
+    /// Use up fuel. Running out flames the engine out.
+    pub fn burn(&mut self, amount: f32) {
+        self.amount = (self.amount - amount).max(0.0);
+        if self.amount == 0.0 {
+            self.flamed_out = true;
+        }
+    }
+
+    pub fn refill(&mut self, amount: f32) {
+        self.amount = (self.amount + amount).min(MAX_FUEL);
+        if self.amount >= RESTART_LEVEL {
+            self.flamed_out = false;
+        }
+    }
+}
+
+/// Let fuel trickle back into every tank.
+pub fn regenerate_fuel(time: Res<Time>, mut tanks: Query<&mut Fuel>) {
+    for mut fuel in tanks.iter_mut() {
+        if fuel.amount < MAX_FUEL {
+            fuel.refill(REGEN_RATE * time.delta_seconds());
+        }
+    }
+}
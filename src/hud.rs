@@ -0,0 +1,292 @@
+//! The heads-up display shown during play: each player's score, lives
+//! and fuel along the top in their trail colour, the round clock along the
+//! bottom, arrows at the edge of the screen pointing at ships and salvage
+//! that have drifted out of sight in the wider arena, and popups where
+//! points are scored.
+
+use crate::{
+    centered_text_bundle,
+    components::{Player, Salvage},
+    AssetManager, Fuel, Localization, MatchRules, MatchStats, PlayGameElement, Settings,
+};
+use bevy::{prelude::*, utils::HashSet};
+
+const HALF_WIDTH: f32 = 512.0;
+const HALF_HEIGHT: f32 = 384.0;
+/// In front of the playfield, and behind the pause overlay.
+const HUD_DEPTH: f32 = 5.0;
+/// How far arrows sit inside the edge of the screen.
+const ARROW_INSET: f32 = 16.0;
+/// Seconds a popup lasts, and how fast it rises.
+const POPUP_TIME: f32 = 1.0;
+const POPUP_RISE: f32 = 40.0;
+/// The size of a fuel gauge, under a player's score.
+const GAUGE_WIDTH: f32 = 100.0;
+const GAUGE_HEIGHT: f32 = 6.0;
+/// The clock turns red when a timed round has this many seconds left.
+const CLOCK_WARNING: f32 = 10.0;
+
+/// A player's score and lives.
+#[derive(Component)]
+pub struct HudScore(usize);
+
+#[derive(Component)]
+pub struct HudClock;
+
+/// The filled part of a player's fuel gauge.
+#[derive(Component)]
+pub struct HudFuelGauge(usize);
+
+/// Points at `target` while it's off the screen.
+#[derive(Component)]
+pub struct OffscreenArrow {
+    target: Entity,
+}
+
+/// Text that floats up and fades away.
+#[derive(Component)]
+pub struct Popup(Timer);
+
+/// Spawn the score panels and the clock, for a round of `player_count`,
+/// with fuel gauges if fuel is in use.
+pub fn spawn_hud(commands: &mut Commands, assets: &AssetManager, player_count: usize, fuel: bool) {
+    for id in 0..player_count {
+        // Spread the panels evenly across the top of the screen.
+        let x = ((id as f32 + 0.5) / player_count as f32 - 0.5) * HALF_WIDTH * 2.0;
+        commands
+            .spawn_bundle(centered_text_bundle(
+                "",
+                assets.default_font.clone(),
+                20.0,
+                Color::WHITE,
+                Vec3::new(x, HALF_HEIGHT - 30.0, HUD_DEPTH),
+            ))
+            .insert(HudScore(id))
+            .insert(PlayGameElement);
+
+        if fuel {
+            let y = HALF_HEIGHT - 62.0;
+            for (color, gauge) in [(Color::DARK_GRAY, None), (Color::WHITE, Some(id))] {
+                let mut sprite = TextureAtlasSprite::new(assets.sprite_index("bar"));
+                sprite.color = color;
+                sprite.custom_size = Some(Vec2::new(GAUGE_WIDTH, GAUGE_HEIGHT));
+                let mut bar = commands.spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: assets.atlases[0].clone(),
+                    sprite,
+                    transform: Transform::from_xyz(x, y, HUD_DEPTH),
+                    ..Default::default()
+                });
+                bar.insert(PlayGameElement);
+                if let Some(id) = gauge {
+                    bar.insert(HudFuelGauge(id));
+                }
+            }
+        }
+    }
+    commands
+        .spawn_bundle(centered_text_bundle(
+            "",
+            assets.default_font.clone(),
+            24.0,
+            Color::WHITE,
+            Vec3::new(0.0, -HALF_HEIGHT + 24.0, HUD_DEPTH),
+        ))
+        .insert(HudClock)
+        .insert(PlayGameElement);
+}
+
+/// Spawn a popup, such as "+1", at `position`.
+pub fn spawn_popup(
+    commands: &mut Commands,
+    assets: &AssetManager,
+    position: Vec3,
+    text: &str,
+    color: Color,
+) {
+    commands
+        .spawn_bundle(centered_text_bundle(
+            text,
+            assets.font_for(text),
+            20.0,
+            color,
+            Vec3::new(position.x, position.y + 20.0, HUD_DEPTH),
+        ))
+        .insert(Popup(Timer::from_seconds(POPUP_TIME, false)))
+        .insert(PlayGameElement);
+}
+
+/// Replace a text's value (and font) and colour, only when they change,
+/// so the text isn't laid out again every frame.
+fn set_text(mut text: Mut<Text>, value: String, color: Color, assets: &AssetManager) {
+    let section = &text.sections[0];
+    if section.value == value && section.style.color == color {
+        return;
+    }
+    let section = &mut text.sections[0];
+    section.style.font = assets.font_for(&value);
+    section.style.color = color;
+    section.value = value;
+}
+
+/// Keep the scores, lives and clock up to date. A timed round counts
+/// down; an untimed one counts up.
+pub fn update_hud(
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    stats: Res<MatchStats>,
+    rules: Res<MatchRules>,
+    mut scores: Query<(&HudScore, &mut Text), Without<HudClock>>,
+    mut clocks: Query<&mut Text, With<HudClock>>,
+) {
+    for (score, text) in scores.iter_mut() {
+        let player = if let Some(player) = stats.players.get(score.0) {
+            player
+        } else {
+            continue;
+        };
+        let status = if player.eliminated.is_some() {
+            localization.text("hud.out")
+        } else {
+            localization.format("hud.lives", &[("lives", player.lives.to_string())])
+        };
+        let value = format!(
+            "{}\n{}",
+            localization.format(
+                "hud.score",
+                &[
+                    ("name", settings.player_name(score.0, &localization)),
+                    ("score", player.score.to_string()),
+                ],
+            ),
+            status
+        );
+        set_text(text, value, settings.player_color(score.0), &assets);
+    }
+
+    let (seconds, color) = match rules.time_limit {
+        Some(limit) => {
+            let left = (limit - stats.elapsed).max(0.0);
+            let color = if left <= CLOCK_WARNING {
+                Color::RED
+            } else {
+                Color::WHITE
+            };
+            (left.ceil() as u32, color)
+        }
+        None => (stats.elapsed as u32, Color::WHITE),
+    };
+    let value = format!("{}:{:02}", seconds / 60, seconds % 60);
+    for text in clocks.iter_mut() {
+        set_text(text, value.clone(), color, &assets);
+    }
+}
+
+/// Fill each fuel gauge from the left, in the player's colour. A player
+/// waiting to respawn keeps their last reading.
+pub fn update_fuel_gauges(
+    settings: Res<Settings>,
+    tanks: Query<(&Player, &Fuel)>,
+    mut gauges: Query<(&HudFuelGauge, &mut TextureAtlasSprite, &mut Transform)>,
+) {
+    for (player, fuel) in tanks.iter() {
+        for (gauge, mut sprite, mut transform) in gauges.iter_mut() {
+            if gauge.0 != player.id {
+                continue;
+            }
+            let left =
+                transform.translation.x - sprite.custom_size.map_or(0.0, |size| size.x) / 2.0;
+            let width = GAUGE_WIDTH * fuel.level();
+            sprite.custom_size = Some(Vec2::new(width, GAUGE_HEIGHT));
+            sprite.color = if fuel.can_thrust() {
+                settings.player_color(player.id)
+            } else {
+                Color::RED
+            };
+            transform.translation.x = left + width / 2.0;
+        }
+    }
+}
+
+/// Is a point in the arena outside the screen?
+fn offscreen(point: Vec2) -> bool {
+    point.x.abs() > HALF_WIDTH || point.y.abs() > HALF_HEIGHT
+}
+
+/// Place an arrow at the edge of the screen nearest `target`, pointing at it.
+fn place_arrow(transform: &mut Transform, target: Vec2) {
+    let edge = Vec2::new(
+        target
+            .x
+            .clamp(-HALF_WIDTH + ARROW_INSET, HALF_WIDTH - ARROW_INSET),
+        target
+            .y
+            .clamp(-HALF_HEIGHT + ARROW_INSET, HALF_HEIGHT - ARROW_INSET),
+    );
+    // The sprite points up; turn it to point from the edge to the target.
+    let direction = target - edge;
+    transform.translation = edge.extend(HUD_DEPTH);
+    transform.rotation = Quat::from_rotation_z((-direction.x).atan2(direction.y));
+}
+
+/// Point an arrow at each ship and piece of salvage that's out of sight,
+/// from the nearest edge of the screen, for as long as it's out of sight.
+#[allow(clippy::type_complexity)]
+pub fn point_offscreen_arrows(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    settings: Res<Settings>,
+    targets: Query<
+        (Entity, &Transform, Option<&Player>),
+        (Or<(With<Player>, With<Salvage>)>, Without<OffscreenArrow>),
+    >,
+    mut arrows: Query<(Entity, &OffscreenArrow, &mut Transform)>,
+) {
+    let mut pointed_at = HashSet::default();
+    for (entity, arrow, mut transform) in arrows.iter_mut() {
+        match targets.get(arrow.target) {
+            Ok((_, target, _)) if offscreen(target.translation.truncate()) => {
+                place_arrow(&mut transform, target.translation.truncate());
+                pointed_at.insert(arrow.target);
+            }
+            _ => commands.entity(entity).despawn(),
+        }
+    }
+
+    for (entity, target, player) in targets.iter() {
+        let target = target.translation.truncate();
+        if pointed_at.contains(&entity) || !offscreen(target) {
+            continue;
+        }
+        let mut sprite = TextureAtlasSprite::new(assets.sprite_index("arrow"));
+        sprite.color = player.map_or(Color::WHITE, |player| settings.player_color(player.id));
+        let mut transform = Transform::default();
+        place_arrow(&mut transform, target);
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: assets.atlases[0].clone(),
+                sprite,
+                transform,
+                ..Default::default()
+            })
+            .insert(OffscreenArrow { target: entity })
+            .insert(PlayGameElement);
+    }
+}
+
+/// Float popups upwards, fading them out.
+pub fn update_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        popup.0.tick(time.delta());
+        if popup.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE * time.delta_seconds();
+        text.sections[0].style.color.set_a(1.0 - popup.0.percent());
+    }
+}
@@ -0,0 +1,162 @@
+//! Ships have a number of lives. Losing one blows the ship up; after a
+//! delay it comes back somewhere safe, and can't be hurt for a moment.
+//! Losing the last one eliminates the player, which ends the round or
+//! knocks them out of it, depending on the `MatchRules`.
+
+use crate::{
+    components::Player, spawn_particle_burst, spawn_ship, AssetManager, EliminationCause,
+    MatchStats, Settings,
+};
+use bevy::prelude::*;
+
+/// Seconds between losing a life and coming back.
+const RESPAWN_DELAY: f32 = 2.0;
+/// Seconds a returning ship is safe for.
+const INVULNERABLE_TIME: f32 = 2.0;
+/// Ships return somewhere on a circle this far from the black hole.
+const SPAWN_RADIUS: f32 = 300.0;
+const SPAWN_POINTS: usize = 12;
+
+/// Sent when a ship is destroyed. Sending this, rather than despawning the
+/// ship, lets the lives system take care of what happens next.
+pub struct ShipDestroyed {
+    pub ship: Entity,
+    pub player: usize,
+    pub position: Vec3,
+    pub cause: EliminationCause,
+}
+
+/// Sent when a player loses their last life.
+pub struct PlayerEliminated {
+    pub player: usize,
+    pub cause: EliminationCause,
+}
+
+/// A ship that can't be destroyed or bumped. It blinks until it wears off.
+#[derive(Component)]
+pub struct Invulnerable(Timer);
+
+impl Default for Invulnerable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Invulnerable {
+    pub fn new() -> Self {
+        Self(Timer::from_seconds(INVULNERABLE_TIME, false))
+    }
+}
+
+struct PendingRespawn {
+    player: usize,
+    timer: Timer,
+}
+
+/// Players waiting to come back. Replaced when a round starts.
+#[derive(Default)]
+pub struct PendingRespawns(Vec<PendingRespawn>);
+
+/// Explode destroyed ships, and take a life from their player.
+pub fn lose_lives(
+    mut commands: Commands,
+    mut destroyed: EventReader<ShipDestroyed>,
+    mut eliminated: EventWriter<PlayerEliminated>,
+    mut stats: ResMut<MatchStats>,
+    mut respawns: ResMut<PendingRespawns>,
+    assets: Res<AssetManager>,
+    settings: Res<Settings>,
+) {
+    for event in destroyed.iter() {
+        commands.entity(event.ship).despawn();
+        spawn_particle_burst(
+            &assets,
+            &mut commands,
+            event.position,
+            Color::ORANGE_RED,
+            Color::BLACK,
+            1500.0,
+            settings.particle_density,
+        );
+
+        let player = if let Some(player) = stats.players.get_mut(event.player) {
+            player
+        } else {
+            continue;
+        };
+        if player.lose_life() {
+            respawns.0.push(PendingRespawn {
+                player: event.player,
+                timer: Timer::from_seconds(RESPAWN_DELAY, false),
+            });
+        } else {
+            player.eliminated = Some(event.cause);
+            eliminated.send(PlayerEliminated {
+                player: event.player,
+                cause: event.cause,
+            });
+        }
+    }
+}
+
+/// Bring ships back once their delay is up, as far as possible from the
+/// other ships.
+pub fn respawn_ships(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut respawns: ResMut<PendingRespawns>,
+    assets: Res<AssetManager>,
+    settings: Res<Settings>,
+    ships: Query<&Transform, With<Player>>,
+) {
+    let mut occupied: Vec<Vec3> = ships.iter().map(|ship| ship.translation).collect();
+    let mut ready = Vec::new();
+    respawns.0.retain_mut(|pending| {
+        pending.timer.tick(time.delta());
+        if pending.timer.finished() {
+            ready.push(pending.player);
+        }
+        !pending.timer.finished()
+    });
+    for player in ready {
+        let position = safe_spawn_point(&occupied);
+        occupied.push(position);
+        spawn_ship(&mut commands, &assets, &settings, player, position).insert(Invulnerable::new());
+    }
+}
+
+/// The spawn point furthest from every ship in `ships`.
+pub fn safe_spawn_point(ships: &[Vec3]) -> Vec3 {
+    (0..SPAWN_POINTS)
+        .map(|i| {
+            let angle = (i as f32 * 360.0 / SPAWN_POINTS as f32).to_radians();
+            Vec3::new(angle.cos(), angle.sin(), 0.0) * SPAWN_RADIUS
+        })
+        .map(|point| {
+            let clearance = ships
+                .iter()
+                .map(|ship| ship.distance(point))
+                .fold(f32::MAX, f32::min);
+            (point, clearance)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(Vec3::new(SPAWN_RADIUS, 0.0, 0.0), |(point, _)| point)
+}
+
+/// Blink invulnerable ships, and make them vulnerable again in time.
+pub fn wear_off_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ships: Query<(Entity, &mut Invulnerable, &mut TextureAtlasSprite)>,
+) {
+    for (entity, mut invulnerable, mut sprite) in ships.iter_mut() {
+        invulnerable.0.tick(time.delta());
+        if invulnerable.0.finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+            sprite.color.set_a(1.0);
+        } else {
+            let blink_on = ((invulnerable.0.elapsed_secs() * 10.0) as u32).is_multiple_of(2);
+            sprite.color.set_a(if blink_on { 0.3 } else { 1.0 });
+        }
+    }
+}
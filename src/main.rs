@@ -1,162 +1,425 @@
 use bevy::prelude::*;
 use std::time::Duration;
 mod components;
-use components::{EmitTrail, PlayGameElement, Player, Salvage};
+mod controls;
+use components::{EmitTrail, Player, Salvage};
+pub use controls::*;
+mod animation;
+pub use animation::{
+    Animation, AnimationClip, AnimationMode, AnimationPlugin, AnimationState, AnimationStateMachine,
+};
 mod asset_manager;
-pub use asset_manager::{AssetManager, BasicAssetPlugin};
+#[cfg(feature = "embedded_assets")]
+pub use asset_manager::EmbeddedAssetIoPlugin;
+pub use asset_manager::{
+    AssetManager, BasicAssetPlugin, Localization, ScreenAsset, ScreenBackground, ScreenFill,
+};
+mod bots;
+pub use bots::*;
+mod fuel;
+pub use fuel::*;
+mod hud;
+pub use hud::*;
+mod lives;
+pub use lives::*;
+mod match_rules;
+pub use match_rules::*;
+mod match_stats;
+pub use match_stats::*;
 mod menu_framework;
-pub use menu_framework::{BasicGamePlugin, GameMode, GameOverResource};
+pub use menu_framework::*;
 mod particles;
 pub use particles::{ParticleColorLerp, ParticleLifetime, ParticlePlugin};
+// The module's doc comment is followed by a blank line, which recent
+// clippy reads as a stray item doc.
+#[allow(clippy::empty_line_after_doc_comments)]
 mod physics;
 pub use physics::{
-    apply_velocity, find_one_collision, velocity_attractor_2d, Attractor, BoundingBox2D, Velocity,
+    apply_velocity, find_one_collision, velocity_attractor_2d, Attractor, BoundingBox2D,
+    GravityScale, Velocity,
 };
+mod powerups;
+pub use powerups::*;
 mod random;
 pub use random::*;
 pub mod centered_text;
 pub use centered_text::*;
 mod remove_elements;
 pub use remove_elements::remove_matching_elements;
+mod salvage;
+pub use salvage::*;
+mod settings;
+pub use settings::*;
+mod weapons;
+pub use weapons::*;
+
+/// The arena reaches past the edges of the screen, so ships and salvage
+/// can drift out of sight, where the HUD's arrows point at them.
+pub const ARENA_WIDTH: f32 = 1280.0;
+pub const ARENA_HEIGHT: f32 = 960.0;
 
 pub struct SalvageTimer(Timer);
 
 pub struct ParticleTimer(pub Timer);
 
-pub struct Scores(Vec<u32>);
-
 fn main() {
-    let assets = BasicAssetPlugin::new().with_atlas("spritesheet.png", 24.0, 24.0, 5, 1);
+    let settings = Settings::load();
+
+    let assets = BasicAssetPlugin::new()
+        .with_packed_atlas("spritesheet.atlas.ron")
+        .with_sprite_clip("ship0_idle", &["ship0"], 0.0, AnimationMode::Loop)
+        .with_sprite_clip(
+            "ship0_thrust",
+            &["ship0_thrust_1", "ship0_thrust_2"],
+            80.0,
+            AnimationMode::Loop,
+        )
+        .with_sprite_clip(
+            "ship0_hit",
+            &["ship0_hit", "ship0", "ship0_hit", "ship0", "ship0_hit"],
+            60.0,
+            AnimationMode::Once,
+        )
+        .with_sprite_clip("ship1_idle", &["ship1"], 0.0, AnimationMode::Loop)
+        .with_sprite_clip(
+            "ship1_thrust",
+            &["ship1_thrust_1", "ship1_thrust_2"],
+            80.0,
+            AnimationMode::Loop,
+        )
+        .with_sprite_clip(
+            "ship1_hit",
+            &["ship1_hit", "ship1", "ship1_hit", "ship1", "ship1_hit"],
+            60.0,
+            AnimationMode::Once,
+        )
+        .with_sprite_clip(
+            "black_hole",
+            &[
+                "black_hole_swirl_1",
+                "black_hole_swirl_2",
+                "black_hole_swirl_3",
+                "black_hole_swirl_4",
+            ],
+            120.0,
+            AnimationMode::Loop,
+        )
+        .with_language("en", "lang/en.lang.ron")
+        .with_language("de", "lang/de.lang.ron")
+        .with_language("ru", "lang/ru.lang.ron")
+        .with_language("ka", "lang/ka.lang.ron")
+        .with_fallback_font("DejaVuSans.ttf")
+        .with_screen(
+            "main_menu",
+            ScreenBackground::solid(Color::BLACK, 1024.0, 768.0)
+                .with_text("title", 60.0, Color::WHITE, Vec3::new(0.0, 200.0, 0.0))
+                .with_text("subtitle", 24.0, Color::GREEN, Vec3::new(0.0, 120.0, 0.0)),
+        )
+        .with_screen(
+            "game_over",
+            ScreenBackground::solid(Color::BLACK, 1024.0, 768.0).with_text(
+                "game_over.title",
+                48.0,
+                Color::WHITE,
+                Vec3::new(0.0, 230.0, 0.0),
+            ),
+        )
+        .with_screen(
+            "pause",
+            ScreenBackground::solid(Color::rgba(0.0, 0.0, 0.0, 0.7), 1024.0, 768.0).with_text(
+                "pause.title",
+                40.0,
+                Color::WHITE,
+                Vec3::new(0.0, 120.0, 0.0),
+            ),
+        )
+        .with_screen(
+            "options",
+            ScreenBackground::solid(Color::rgba(0.0, 0.0, 0.1, 0.9), 1024.0, 768.0).with_text(
+                "options.title",
+                40.0,
+                Color::WHITE,
+                Vec3::new(0.0, 200.0, 0.0),
+            ),
+        )
+        .with_screen(
+            "players",
+            ScreenBackground::solid(Color::rgba(0.0, 0.0, 0.1, 1.0), 1024.0, 768.0).with_text(
+                "players.title",
+                40.0,
+                Color::WHITE,
+                Vec3::new(0.0, 200.0, 0.0),
+            ),
+        )
+        .with_screen(
+            "controls",
+            ScreenBackground::solid(Color::rgba(0.0, 0.0, 0.1, 1.0), 1024.0, 768.0).with_text(
+                "controls.title",
+                40.0,
+                Color::WHITE,
+                Vec3::new(0.0, 310.0, 0.0),
+            ),
+        )
+        .with_screen(
+            "rules",
+            ScreenBackground::solid(Color::rgba(0.0, 0.0, 0.1, 1.0), 1024.0, 768.0).with_text(
+                "rules.title",
+                40.0,
+                Color::WHITE,
+                Vec3::new(0.0, 200.0, 0.0),
+            ),
+        )
+        .with_screen(
+            "intermission",
+            ScreenBackground::solid(Color::BLACK, 1024.0, 768.0).with_text(
+                "intermission.title",
+                48.0,
+                Color::WHITE,
+                Vec3::new(0.0, 230.0, 0.0),
+            ),
+        )
+        .with_screen(
+            "confirm",
+            ScreenBackground::solid(Color::rgb(0.1, 0.1, 0.25), 600.0, 220.0),
+        )
+        .with_hot_reload(cfg!(all(
+            debug_assertions,
+            not(feature = "embedded_assets")
+        )));
 
     App::new()
         .insert_resource(WindowDescriptor {
             title: "Gravity Well".to_string(),
-            width: 1024.0,
-            height: 768.0,
+            width: settings.window_width,
+            height: settings.window_height,
             resizable: false,
             ..Default::default()
         })
-        .add_plugins(DefaultPlugins)
+        .add_plugins_with(DefaultPlugins, |group| {
+            // Embedded assets replace the asset server's file access.
+            #[cfg(feature = "embedded_assets")]
+            group.add_before::<bevy::asset::AssetPlugin, _>(EmbeddedAssetIoPlugin);
+            group
+        })
+        .insert_resource(settings)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(ControlsPlugin)
         .add_plugin(BasicGamePlugin)
         .add_plugin(assets)
         .add_plugin(ParticlePlugin)
+        .add_plugin(AnimationPlugin)
         .insert_resource(RandomNumbers::new())
         .insert_resource(ClearColor(Color::BLACK))
+        .init_resource::<MatchRules>()
+        .add_event::<ShipDestroyed>()
+        .add_event::<PlayerEliminated>()
         .add_system_set(SystemSet::on_enter(GameMode::Playing).with_system(setup_play_game))
         .add_system_set(
             SystemSet::on_update(GameMode::Playing)
-                .with_system(player_control)
+                .with_system(drive_bots.before("player_control"))
+                .with_system(player_control.label("player_control"))
                 .with_system(apply_velocity)
                 .with_system(velocity_attractor_2d)
                 .with_system(trails)
                 .with_system(bounce)
-                .with_system(black_hole)
+                .with_system(shield_from_black_hole.before("black_hole"))
+                .with_system(black_hole.label("black_hole"))
+                .with_system(lose_lives.label("lose_lives").after("black_hole"))
+                .with_system(respawn_ships)
+                .with_system(wear_off_invulnerability)
                 .with_system(spawn_salvage)
+                .with_system(materialise_arrivals)
                 .with_system(clamp_positions)
                 .with_system(collect_salvage)
-                .with_system(end_game),
+                .with_system(detonate_volatile_salvage)
+                .with_system(spawn_power_ups)
+                .with_system(collect_power_ups)
+                .with_system(wear_off_status_effects.before("lose_lives"))
+                .with_system(magnetise_salvage)
+                .with_system(show_status_effects)
+                .with_system(fire_weapons.after("player_control"))
+                .with_system(expire_projectiles)
+                .with_system(projectile_hits)
+                .with_system(track_match)
+                .with_system(regenerate_fuel)
+                .with_system(update_hud)
+                .with_system(update_fuel_gauges)
+                .with_system(update_popups)
+                .with_system(point_offscreen_arrows)
+                .with_system(end_game.after("lose_lives")),
         )
-        .add_system_set(SystemSet::on_exit(GameMode::Playing).with_system(exit_play_game))
         .run();
 }
 
 /// Runs when "play game" becomes the active mode. Spawns all initial
-/// entities required for game play.
-fn setup_play_game(mut commands: Commands, assets: Res<AssetManager>) {
-    // Spawn a 2D camera
-    commands
-        .spawn_bundle(OrthographicCameraBundle::new_2d())
-        .insert(PlayGameElement);
-
-    // Spawn player 0
-    commands
-        .spawn_bundle(bevy::prelude::SpriteSheetBundle {
-            texture_atlas: assets.atlases[0].clone(),
-            sprite: TextureAtlasSprite::new(0),
-            transform: Transform::from_xyz(300.0, 1.0, 0.0),
-            ..Default::default()
-        })
-        .insert(PlayGameElement)
-        .insert(Velocity(Vec3::new(0.0, 2.0, 0.0)))
-        .insert(Player { id: 0 })
-        .insert(EmitTrail)
-        .insert(BoundingBox2D::new(24.0, 24.0));
+/// entities required for game play, and starts a new match unless this is
+/// the next round of one.
+fn setup_play_game(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    settings: Res<Settings>,
+    rules: Res<MatchRules>,
+    series: Option<Res<Series>>,
+) {
+    // A new match uses the latest rules and player count; later rounds
+    // keep the match's.
+    let (rules, player_count) = match series {
+        Some(series) if series.winner(&rules).is_none() => (rules.clone(), series.wins.len()),
+        _ => {
+            let player_count = settings.player_count.clamp(2, MAX_PLAYERS);
+            commands.insert_resource(settings.rules.clone());
+            commands.insert_resource(Series::new(player_count));
+            (settings.rules.clone(), player_count)
+        }
+    };
+    commands.remove_resource::<MatchResult>();
 
-    // Spawn player 1
-    commands
-        .spawn_bundle(bevy::prelude::SpriteSheetBundle {
-            texture_atlas: assets.atlases[0].clone(),
-            sprite: TextureAtlasSprite::new(1),
-            transform: Transform::from_xyz(-300.0, 1.0, 0.0),
-            ..Default::default()
-        })
-        .insert(PlayGameElement)
-        .insert(Velocity(Vec3::new(0.0, -2.0, 0.0)))
-        .insert(Player { id: 1 })
-        .insert(EmitTrail)
-        .insert(BoundingBox2D::new(24.0, 24.0));
+    // Spread the players evenly around the black hole
+    for id in 0..player_count {
+        let angle = (id as f32 * 360.0 / player_count as f32).to_radians();
+        let position = Vec3::new(angle.cos() * 300.0, angle.sin() * 300.0, 0.0);
+        spawn_ship(&mut commands, &assets, &settings, id, position);
+    }
 
     // Spawn the black hole in the middle
     commands
         .spawn_bundle(bevy::prelude::SpriteSheetBundle {
             texture_atlas: assets.atlases[0].clone(),
-            sprite: TextureAtlasSprite::new(3),
+            sprite: TextureAtlasSprite::new(assets.sprite_index("black_hole")),
             transform: Transform::from_xyz(0.0, 0.0, 0.0),
             ..Default::default()
         })
         .insert(Attractor { max_velocity: 3.0 })
         .insert(PlayGameElement)
-        .insert(BoundingBox2D::new(24.0, 24.0));
+        .insert(BoundingBox2D::new(24.0, 24.0))
+        .insert(Animation::new("black_hole"));
 
-    // Add the two timers the game uses
-    commands.insert_resource(ParticleTimer(Timer::new(Duration::from_millis(10), true)));
+    // Add the timers the game uses
+    let trail_interval = 10.0 / settings.particle_density;
+    commands.insert_resource(ParticleTimer(Timer::new(
+        Duration::from_secs_f32(trail_interval / 1000.0),
+        true,
+    )));
     commands.insert_resource(SalvageTimer(Timer::new(Duration::from_millis(2000), true)));
+    commands.insert_resource(PowerUpTimer(Timer::from_seconds(POWER_UP_INTERVAL, true)));
+
+    // Start recording the round
+    commands.insert_resource(MatchStats::new(player_count, rules.lives));
+    commands.insert_resource(PendingRespawns::default());
+    spawn_hud(&mut commands, &assets, player_count, rules.fuel);
+}
+
+/// Spawn a player's ship at `position`, circling the black hole, in the
+/// design chosen for their slot. A bot takes the controls if the slot is
+/// set to one.
+fn spawn_ship<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    assets: &AssetManager,
+    settings: &Settings,
+    id: usize,
+    position: Vec3,
+) -> bevy::ecs::system::EntityCommands<'w, 's, 'a> {
+    let design = settings.player_ship(id);
+    let orbit = Vec3::new(-position.y, position.x, 0.0).normalize_or_zero() * 2.0;
+    let mut ship = commands.spawn_bundle(bevy::prelude::SpriteSheetBundle {
+        texture_atlas: assets.atlases[0].clone(),
+        sprite: TextureAtlasSprite::new(assets.sprite_index(design)),
+        transform: Transform::from_translation(position),
+        ..Default::default()
+    });
+    ship.insert(PlayGameElement)
+        .insert(Velocity(orbit))
+        .insert(Player { id })
+        .insert(EmitTrail)
+        .insert(BoundingBox2D::new(24.0, 24.0))
+        .insert(Weapon::default())
+        .insert(Fuel::default())
+        .insert(StatusEffects::default())
+        .insert_bundle(ship_animations(design));
+    if let Some(difficulty) = settings.players.get(id).and_then(|slot| slot.bot) {
+        ship.insert(Bot::new(difficulty));
+    }
+    ship
+}
 
-    // Store the scores
-    commands.insert_resource(Scores(vec![0, 0]));
+/// Builds the animation components for a ship, using the `<design>_*`
+/// clips registered with the asset manager.
+fn ship_animations(design: &str) -> (Animation, AnimationStateMachine) {
+    let machine = AnimationStateMachine::new()
+        .with_state(AnimationState::Idle, format!("{}_idle", design))
+        .with_state(AnimationState::Thrusting, format!("{}_thrust", design))
+        .with_state(AnimationState::Hit, format!("{}_hit", design));
+    (machine.initial_animation(), machine)
 }
 
-/// Handle player keyboard input
+/// Handle player input, through each player's bound actions. When the
+/// rules call for fuel, thrust burns it, and a ship that has flamed out
+/// can only turn. A boosted ship has a higher top speed.
 fn player_control(
-    keyboard: Res<Input<KeyCode>>,
-    mut player_query: Query<(&mut Velocity, &mut Transform, &Player)>,
+    time: Res<Time>,
+    actions: Res<ActionInput>,
+    rules: Res<MatchRules>,
+    mut player_query: Query<(
+        &mut Velocity,
+        &mut Transform,
+        &Player,
+        &mut AnimationStateMachine,
+        &mut Fuel,
+        &StatusEffects,
+    )>,
 ) {
-    for (mut velocity, mut trans, player) in player_query.iter_mut() {
-        let (left, right, thrust) = match player.id {
-            0 => (KeyCode::Left, KeyCode::Right, KeyCode::Up),
-            _ => (KeyCode::A, KeyCode::D, KeyCode::W),
-        };
-
-        if keyboard.pressed(left) {
-            trans.rotate(Quat::from_rotation_z(f32::to_radians(2.0)));
-        }
-        if keyboard.pressed(right) {
-            trans.rotate(Quat::from_rotation_z(f32::to_radians(-2.0)));
+    for (mut velocity, mut trans, player, mut animation, mut fuel, effects) in
+        player_query.iter_mut()
+    {
+        // Sticks and triggers are analog, so steering and thrust are scaled
+        // by how far they're pushed. Keys are all-or-nothing.
+        let steer = actions.value(player.id, InputAction::RotateLeft)
+            - actions.value(player.id, InputAction::RotateRight);
+        if steer != 0.0 {
+            trans.rotate(Quat::from_rotation_z(f32::to_radians(2.0 * steer)));
         }
 
-        if keyboard.pressed(thrust) {
-            velocity.0 += trans.local_y() / 10.0;
-            velocity.0 = velocity.0.clamp_length_max(5.0);
+        let mut thrust = actions.value(player.id, InputAction::Thrust);
+        if rules.fuel {
+            if fuel.can_thrust() {
+                fuel.burn(BURN_RATE * thrust * time.delta_seconds());
+            } else {
+                thrust = 0.0;
+            }
+        }
+        if thrust > 0.0 {
+            velocity.0 += trans.local_y() / 10.0 * thrust;
+            let top_speed = if effects.has(StatusEffect::Boost) {
+                BOOST_SPEED
+            } else {
+                5.0
+            };
+            velocity.0 = velocity.0.clamp_length_max(top_speed);
+            animation.set(AnimationState::Thrusting);
+        } else {
+            animation.set(AnimationState::Idle);
         }
     }
 }
 
-/// Anything with an `EmitTrail` component spawns particles periodically.
+/// Anything with an `EmitTrail` component spawns particles periodically,
+/// in a player's colour or a kind of salvage's.
+#[allow(clippy::type_complexity)]
 fn trails(
     mut commands: Commands,
-    query: Query<(&Transform, Option<&Player>), With<EmitTrail>>,
+    query: Query<(&Transform, Option<&Player>, Option<&Salvage>), With<EmitTrail>>,
     time: Res<Time>,
     mut timer: ResMut<ParticleTimer>,
     assets: Res<AssetManager>,
+    settings: Res<Settings>,
 ) {
     timer.0.tick(time.delta());
     if timer.0.finished() {
-        for (pos, player) in query.iter() {
+        for (pos, player, salvage) in query.iter() {
             let (start, end) = if let Some(player) = player {
-                match player.id {
-                    0 => (Color::YELLOW, Color::BLACK),
-                    _ => (Color::PURPLE, Color::BLACK),
-                }
+                (settings.player_color(player.id), Color::BLACK)
+            } else if let Some(salvage) = salvage {
+                (salvage.kind.particle_colors().0, Color::BLACK)
             } else {
                 (Color::WHITE, Color::BLACK)
             };
@@ -164,7 +427,7 @@ fn trails(
             commands
                 .spawn_bundle(bevy::prelude::SpriteSheetBundle {
                     texture_atlas: assets.atlases[0].clone(),
-                    sprite: TextureAtlasSprite::new(2),
+                    sprite: TextureAtlasSprite::new(assets.sprite_index("particle")),
                     transform: Transform::from_xyz(
                         pos.translation.x,
                         pos.translation.y,
@@ -181,26 +444,75 @@ fn trails(
 }
 
 /// Did the players hit one another? If so, we'll make them bounce away and spawn a particle
-/// burst.
+/// burst. Invulnerable ships pass through. Heavier ships push lighter ones
+/// further, and a shield takes the knock for its ship, once: the ship stays
+/// out of the bounce until it's clear of the ships it was touching.
+#[allow(clippy::type_complexity)]
 fn bounce(
-    mut query: Query<(
-        &mut BoundingBox2D,
-        &mut Transform,
-        &mut Velocity,
-        &mut Player,
-    )>,
+    mut query: Query<
+        (
+            Entity,
+            &mut BoundingBox2D,
+            &mut Transform,
+            &mut Velocity,
+            &mut AnimationStateMachine,
+            &Player,
+            &mut StatusEffects,
+            Option<&Shielding>,
+        ),
+        Without<Invulnerable>,
+    >,
     mut commands: Commands,
     assets: Res<AssetManager>,
+    settings: Res<Settings>,
+    mut stats: ResMut<MatchStats>,
 ) {
+    let mut overlapping = Vec::new();
     let mut combinations = query.iter_combinations_mut();
-    while let Some([(box_a, pos_a, mut velocity_a, _p1), (box_b, pos_b, mut velocity_b, _p2)]) =
-        combinations.fetch_next()
+    while let Some(
+        [(ship_a, box_a, pos_a, mut velocity_a, mut anim_a, player_a, mut effects_a, shielding_a), (ship_b, box_b, pos_b, mut velocity_b, mut anim_b, player_b, mut effects_b, shielding_b)],
+    ) = combinations.fetch_next()
     {
         // mutably access components data
-        if box_a.collides_with(&*pos_a, &box_b, &pos_b) {
-            let bounce = (pos_a.translation - pos_b.translation).normalize();
-            velocity_a.0 += bounce;
-            velocity_b.0 -= bounce;
+        if box_a.collides_with(&pos_a, &box_b, &pos_b) {
+            overlapping.push(ship_a);
+            overlapping.push(ship_b);
+            let offset = pos_a.translation - pos_b.translation;
+            // Ships stay overlapped for a few frames; only count the
+            // collision while they're still closing on each other.
+            let closing = (velocity_a.0 - velocity_b.0).dot(offset) < 0.0;
+            if closing {
+                for id in [player_a.id, player_b.id] {
+                    if let Some(player) = stats.players.get_mut(id) {
+                        player.collisions += 1;
+                    }
+                }
+                anim_a.trigger(AnimationState::Hit);
+                anim_b.trigger(AnimationState::Hit);
+            }
+            // Each ship takes a share of the knock by the other's mass, so
+            // two ordinary ships are each pushed the same distance apart.
+            let (mass_a, mass_b) = (effects_a.mass(), effects_b.mass());
+            let bounce = offset.normalize() * 2.0 / (mass_a + mass_b);
+            let mut shielded =
+                |ship, shielding: Option<&Shielding>, effects: &mut StatusEffects| {
+                    if shielding.is_some() {
+                        return true;
+                    }
+                    let used = closing && effects.consume(StatusEffect::Shield);
+                    if used {
+                        commands.entity(ship).insert(Shielding);
+                    }
+                    used
+                };
+            let shielded_a = shielded(ship_a, shielding_a, &mut effects_a);
+            let shielded_b = shielded(ship_b, shielding_b, &mut effects_b);
+            if !shielded_a {
+                velocity_a.0 += bounce * mass_b;
+            }
+            if !shielded_b {
+                velocity_b.0 -= bounce * mass_a;
+            }
             spawn_particle_burst(
                 &assets,
                 &mut commands,
@@ -208,74 +520,198 @@ fn bounce(
                 Color::CYAN,
                 Color::BLUE,
                 1000.0,
+                settings.particle_density,
             );
         }
     }
+
+    for (ship, .., shielding) in query.iter() {
+        if shielding.is_some() && !overlapping.contains(&ship) {
+            commands.entity(ship).remove::<Shielding>();
+        }
+    }
 }
 
-/// Did anything fall into the black hole? That ends the existence of an entity.
+/// Did anything fall into the black hole? Salvage is gone for good; ships
+/// are destroyed, which costs their player a life.
+#[allow(clippy::type_complexity)]
 fn black_hole(
     mut commands: Commands,
     hole_query: Query<(&Transform, &BoundingBox2D), With<Attractor>>,
-    other_query: Query<(Entity, &Transform, &BoundingBox2D), Without<Attractor>>,
+    other_query: Query<
+        (Entity, &Transform, &BoundingBox2D),
+        (Without<Attractor>, Without<Invulnerable>),
+    >,
+    players: Query<&Player>,
+    mut destroyed: EventWriter<ShipDestroyed>,
 ) {
     let objects: Vec<(Entity, &Transform, &BoundingBox2D)> = other_query.iter().collect();
     for (hole_pos, hole_box) in hole_query.iter() {
-        if let Some(destroyed) = find_one_collision(hole_pos, hole_box, &objects) {
-            commands.entity(destroyed).despawn();
+        if let Some(entity) = find_one_collision(hole_pos, hole_box, &objects) {
+            if let Ok(player) = players.get(entity) {
+                let position = objects
+                    .iter()
+                    .find(|(object, _, _)| *object == entity)
+                    .map_or(hole_pos.translation, |(_, pos, _)| pos.translation);
+                destroyed.send(ShipDestroyed {
+                    ship: entity,
+                    player: player.id,
+                    position,
+                    cause: EliminationCause::BlackHole,
+                });
+            } else {
+                commands.entity(entity).despawn();
+            }
         }
     }
 }
 
-/// Periodically add salvage to the game
+/// Periodically pick a spot for some salvage, of a kind picked by the
+/// rules' weights, and put a warning marker there. The spot is kept clear
+/// of the black hole and the ships, and nothing is added while there's as
+/// much salvage about as the rules allow.
+#[allow(clippy::too_many_arguments)]
 fn spawn_salvage(
     mut commands: Commands,
     assets: Res<AssetManager>,
     time: Res<Time>,
     mut timer: ResMut<SalvageTimer>,
     rng: ResMut<RandomNumbers>,
+    rules: Res<MatchRules>,
+    salvage: Query<(), With<Salvage>>,
+    markers: Query<&SpawnMarker>,
+    attractors: Query<&Transform, With<Attractor>>,
+    ships: Query<&Transform, With<Player>>,
 ) {
     timer.0.tick(time.delta());
-    if timer.0.just_finished() {
-        let position = Vec3::new(
-            rng.range(0, 1024) as f32 - 512.0,
-            rng.range(0, 768) as f32 - 384.0,
-            1.0,
-        );
-        let velocity = Vec3::new(
-            (rng.range(0, 20) as f32 - 10.0) / 5.0,
-            (rng.range(0, 20) as f32 - 10.0) / 5.0,
-            0.0,
+    if !timer.0.just_finished() {
+        return;
+    }
+    let policy = &rules.salvage_spawning;
+    let arriving = markers
+        .iter()
+        .filter(|marker| matches!(marker.arrival, Arrival::Salvage(_)))
+        .count();
+    if salvage.iter().count() + arriving >= policy.max_salvage {
+        return;
+    }
+    let wells: Vec<Vec3> = attractors.iter().map(|well| well.translation).collect();
+    let ships: Vec<Vec3> = ships.iter().map(|ship| ship.translation).collect();
+    // If everywhere tried was too crowded, try again next time.
+    if let Some(position) = policy.find_spot(&rng, &wells, &ships) {
+        let kind = rules.salvage_weights.pick(&rng);
+        spawn_marker(
+            &mut commands,
+            &assets,
+            Arrival::Salvage(kind),
+            position,
+            policy.warning_time,
         );
+    }
+}
+
+/// Turn warning markers into salvage or power-ups once their time is up.
+/// Until then, they shrink and pulse. What arrives drifts off at random,
+/// or, if the rules say so, starts in a circular orbit around the nearest
+/// black hole.
+#[allow(clippy::too_many_arguments)]
+fn materialise_arrivals(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    time: Res<Time>,
+    rng: Res<RandomNumbers>,
+    settings: Res<Settings>,
+    rules: Res<MatchRules>,
+    mut markers: Query<(
+        Entity,
+        &mut SpawnMarker,
+        &mut Transform,
+        &mut TextureAtlasSprite,
+    )>,
+    attractors: Query<&Transform, (With<Attractor>, Without<SpawnMarker>)>,
+) {
+    for (entity, mut marker, mut transform, mut sprite) in markers.iter_mut() {
+        marker.timer.tick(time.delta());
+        if !marker.timer.finished() {
+            let progress = marker.timer.percent();
+            transform.scale = Vec3::splat(1.5 - progress);
+            let pulse = ((marker.timer.elapsed_secs() * 8.0) as u32).is_multiple_of(2);
+            sprite.color.set_a(if pulse { 0.9 } else { 0.4 });
+            continue;
+        }
+        commands.entity(entity).despawn();
+
+        let position = transform.translation;
+        let gravity = match marker.arrival {
+            Arrival::Salvage(kind) => kind.gravity(),
+            Arrival::PowerUp(_) => 1.0,
+        };
+        let well = attractors
+            .iter()
+            .map(|well| well.translation)
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+        let velocity = match well {
+            Some(well) if rules.salvage_spawning.orbital && well.distance(position) > 0.0 => {
+                orbital_velocity(position, well, gravity, rng.range(0, 2) == 0)
+            }
+            _ => Vec3::new(
+                (rng.range(0, 20) as f32 - 10.0) / 5.0,
+                (rng.range(0, 20) as f32 - 10.0) / 5.0,
+                0.0,
+            ),
+        };
+
+        let kind = match marker.arrival {
+            Arrival::Salvage(kind) => kind,
+            Arrival::PowerUp(effect) => {
+                spawn_power_up(
+                    &mut commands,
+                    &assets,
+                    &settings,
+                    effect,
+                    position,
+                    velocity,
+                );
+                continue;
+            }
+        };
+        let mut sprite = TextureAtlasSprite::new(assets.sprite_index("salvage"));
+        sprite.color = kind.color();
+        let size = 24.0 * kind.size();
+        sprite.custom_size = Some(Vec2::splat(size));
+        let (start, end) = kind.particle_colors();
         commands
             .spawn_bundle(bevy::prelude::SpriteSheetBundle {
                 texture_atlas: assets.atlases[0].clone(),
-                sprite: TextureAtlasSprite::new(4),
-                transform: Transform::from_xyz(position.x, position.y, position.z),
+                sprite,
+                transform: Transform::from_translation(position),
                 ..Default::default()
             })
             .insert(PlayGameElement)
-            .insert(BoundingBox2D::new(24.0, 24.0))
+            .insert(BoundingBox2D::new(size, size))
             .insert(EmitTrail)
             .insert(Velocity(velocity))
-            .insert(Salvage);
+            .insert(GravityScale(kind.gravity()))
+            .insert(Salvage { kind });
         spawn_particle_burst(
             &assets,
             &mut commands,
             position,
-            Color::PINK,
-            Color::BLACK,
+            start,
+            end,
             1000.0,
+            settings.particle_density,
         );
     }
 }
 
-/// Ensure that nothing flies completely off the screen
+/// Ensure that nothing flies out of the arena
+#[allow(clippy::type_complexity)]
 fn clamp_positions(mut query: Query<&mut Transform, Or<(Changed<Transform>, Added<Transform>)>>) {
-    let left = (0.0 - 1024.0) / 2.0;
-    let right = 1024.0 / 2.0;
-    let top = (0.0 - 768.0) / 2.0;
-    let bottom = 768.0 / 2.0;
+    let left = (0.0 - ARENA_WIDTH) / 2.0;
+    let right = ARENA_WIDTH / 2.0;
+    let top = (0.0 - ARENA_HEIGHT) / 2.0;
+    let bottom = ARENA_HEIGHT / 2.0;
     for mut pos in query.iter_mut() {
         if pos.translation.x < left {
             pos.translation.x = left;
@@ -292,7 +728,8 @@ fn clamp_positions(mut query: Query<&mut Transform, Or<(Changed<Transform>, Adde
     }
 }
 
-/// Spawn a circle of particles at a given point.
+/// Spawn a circle of particles at a given point. At a density of 1.0,
+/// there's one particle per degree.
 fn spawn_particle_burst(
     assets: &AssetManager,
     commands: &mut Commands,
@@ -300,14 +737,17 @@ fn spawn_particle_burst(
     start: Color,
     end: Color,
     lifetime: f32,
+    density: f32,
 ) {
-    for angle in 0..360 {
-        let velocity =
-            Quat::from_rotation_z((angle as f32).to_radians()) * Vec3::new(1.0, 0.0, 0.0);
+    let count = (360.0 * density).round().max(1.0) as usize;
+    let particle = assets.sprite_index("particle");
+    for i in 0..count {
+        let angle = i as f32 * 360.0 / count as f32;
+        let velocity = Quat::from_rotation_z(angle.to_radians()) * Vec3::new(1.0, 0.0, 0.0);
         commands
             .spawn_bundle(bevy::prelude::SpriteSheetBundle {
                 texture_atlas: assets.atlases[0].clone(),
-                sprite: TextureAtlasSprite::new(2),
+                sprite: TextureAtlasSprite::new(particle),
                 transform: Transform::from_xyz(pos.x, pos.y, pos.z),
                 ..Default::default()
             })
@@ -318,50 +758,94 @@ fn spawn_particle_burst(
     }
 }
 
-/// Did either player hit some salvage? If so, give them some score, despawn the salvage and add a particle burst.
+/// Did any player hit some salvage? If so, give them its value in score, despawn the salvage and add a particle burst and a popup.
 fn collect_salvage(
     mut commands: Commands,
-    players: Query<(&Player, &Transform, &BoundingBox2D)>,
-    salvage: Query<(Entity, &Transform, &BoundingBox2D), With<Salvage>>,
-    mut scores: ResMut<Scores>,
+    mut players: Query<(&Player, &Transform, &BoundingBox2D, &mut Fuel)>,
+    salvage: Query<(Entity, &Salvage, &Transform, &BoundingBox2D)>,
+    mut stats: ResMut<MatchStats>,
     assets: Res<AssetManager>,
+    settings: Res<Settings>,
 ) {
-    let salvage: Vec<(Entity, &Transform, &BoundingBox2D)> = salvage.iter().collect();
-    for (player, pos, bounds) in players.iter() {
-        if let Some(salvage) = find_one_collision(pos, bounds, &salvage) {
-            commands.entity(salvage).despawn();
-            scores.0[player.id] += 1;
+    let pieces: Vec<(Entity, &Transform, &BoundingBox2D)> = salvage
+        .iter()
+        .map(|(entity, _, pos, bounds)| (entity, pos, bounds))
+        .collect();
+    for (player, pos, bounds, mut fuel) in players.iter_mut() {
+        if let Some(entity) = find_one_collision(pos, bounds, &pieces) {
+            let kind = if let Ok((_, piece, _, _)) = salvage.get(entity) {
+                piece.kind
+            } else {
+                continue;
+            };
+            commands.entity(entity).despawn();
+            fuel.refill(SALVAGE_REFILL);
+            if let Some(player_stats) = stats.players.get_mut(player.id) {
+                player_stats.score += kind.value();
+                player_stats.salvage += 1;
+            }
+            spawn_popup(
+                &mut commands,
+                &assets,
+                pos.translation,
+                &format!("+{}", kind.value()),
+                settings.player_color(player.id),
+            );
+            let (start, end) = kind.particle_colors();
             spawn_particle_burst(
                 &assets,
                 &mut commands,
                 pos.translation,
-                Color::GREEN,
-                Color::YELLOW,
+                start,
+                end,
                 2000.0,
+                settings.particle_density,
             );
         }
     }
 }
 
-/// Did a player die? If so, end the game.
+/// Has the round been decided, by the clock, the score target or a player
+/// running out of lives? If so, show the results: between rounds, or at
+/// the end of the match once someone has won enough of them.
 fn end_game(
-    query: Query<&Player>,
-    mut app_state: ResMut<State<GameMode>>,
+    mut eliminations: EventReader<PlayerEliminated>,
+    mut screen_commands: EventWriter<ScreenCommand>,
     mut commands: Commands,
-    scores: Res<Scores>,
+    stats: Res<MatchStats>,
+    rules: Res<MatchRules>,
+    mut series: ResMut<Series>,
+    result: Option<Res<MatchResult>>,
 ) {
-    let remaining_players = query.iter().count();
-    if remaining_players < 2 {
-        let mut message = String::new();
-        for (id, score) in scores.0.iter().enumerate() {
-            message += &format!("Player {} scored {} points.\n", id + 1, score);
-        }
-        commands.insert_resource(GameOverResource { message });
-        app_state.set(GameMode::GameOver).unwrap();
+    // The round is already over, and the screen is on its way out.
+    if result.is_some() {
+        return;
+    }
+    let eliminated = eliminations.iter().count() > 0
+        && match rules.on_elimination {
+            EliminationRule::EndsRound => true,
+            EliminationRule::LastShipStanding => {
+                stats
+                    .players
+                    .iter()
+                    .filter(|p| p.eliminated.is_none())
+                    .count()
+                    < 2
+            }
+        };
+    let out_of_time = rules.time_limit.is_some_and(|limit| stats.elapsed >= limit);
+    let target_reached = rules
+        .score_target
+        .is_some_and(|target| stats.players.iter().any(|p| p.score >= target));
+    if eliminated || out_of_time || target_reached {
+        let result = MatchResult::from_stats(&stats, &rules);
+        series.record(&result);
+        let next = if series.winner(&rules).is_some() {
+            GameMode::GameOver
+        } else {
+            GameMode::Intermission
+        };
+        commands.insert_resource(result);
+        screen_commands.send(ScreenCommand::Replace(next));
     }
-}
-
-/// Clean up by removing all gameplay elements on game over.
-fn exit_play_game(mut commands: Commands, elements: Query<(Entity, &PlayGameElement)>) {
-    remove_matching_elements(elements, &mut commands);
 }
@@ -0,0 +1,102 @@
+use crate::{MatchOutcome, MatchResult, SalvageSpawning, SalvageWeights};
+use serde::{Deserialize, Serialize};
+
+/// What happens when a player loses their last life.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EliminationRule {
+    /// The round ends at once, and the highest score wins it.
+    EndsRound,
+    /// The player is out for the rest of the round. The round ends when
+    /// one player is left, and that player wins it.
+    LastShipStanding,
+}
+
+impl EliminationRule {
+    pub const ALL: [EliminationRule; 2] = [
+        EliminationRule::EndsRound,
+        EliminationRule::LastShipStanding,
+    ];
+
+    /// The string key of the rule's name.
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            EliminationRule::EndsRound => "rules.ends_round",
+            EliminationRule::LastShipStanding => "rules.last_ship_standing",
+        }
+    }
+}
+
+/// How a match is won. Edited on the rules screen and saved with the
+/// settings; copied into a resource when a match starts, so changing the
+/// rules from the pause menu doesn't affect the match in progress.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchRules {
+    /// Seconds in a round, if rounds are timed.
+    pub time_limit: Option<f32>,
+    /// A score that wins the round as soon as someone reaches it.
+    pub score_target: Option<u32>,
+    /// Rounds a player must win to take the match.
+    pub rounds_to_win: u32,
+    /// Ships each player gets per round.
+    pub lives: u32,
+    pub on_elimination: EliminationRule,
+    /// Whether ships can fire.
+    pub weapons: bool,
+    /// Whether thrust burns fuel.
+    pub fuel: bool,
+    /// The mix of salvage kinds.
+    pub salvage_weights: SalvageWeights,
+    /// Where and how salvage appears.
+    pub salvage_spawning: SalvageSpawning,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            time_limit: Some(180.0),
+            score_target: Some(10),
+            rounds_to_win: 2,
+            lives: 3,
+            on_elimination: EliminationRule::EndsRound,
+            weapons: false,
+            fuel: true,
+            salvage_weights: SalvageWeights::default(),
+            salvage_spawning: SalvageSpawning::default(),
+        }
+    }
+}
+
+/// The rounds of a match played so far.
+pub struct Series {
+    /// Rounds won by each player.
+    pub wins: Vec<u32>,
+    /// The number of the round being played, from 1.
+    pub round: u32,
+}
+
+impl Series {
+    pub fn new(player_count: usize) -> Self {
+        Self {
+            wins: vec![0; player_count],
+            round: 1,
+        }
+    }
+
+    /// Count a finished round. A tied round is won by nobody.
+    pub fn record(&mut self, result: &MatchResult) {
+        if let MatchOutcome::Winner(id) = result.outcome {
+            if let Some(wins) = self.wins.get_mut(id) {
+                *wins += 1;
+            }
+        }
+        self.round += 1;
+    }
+
+    /// The player who has won enough rounds to take the match, if any.
+    pub fn winner(&self, rules: &MatchRules) -> Option<usize> {
+        self.wins
+            .iter()
+            .position(|wins| *wins >= rules.rounds_to_win.max(1))
+    }
+}
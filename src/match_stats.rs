@@ -0,0 +1,123 @@
+use crate::{components::Player, EliminationRule, MatchRules};
+use bevy::prelude::*;
+
+/// Why a player left the match early.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EliminationCause {
+    BlackHole,
+}
+
+impl EliminationCause {
+    /// The string key describing the cause, for the scoreboard.
+    pub fn describe_key(&self) -> &'static str {
+        match self {
+            EliminationCause::BlackHole => "fate.black_hole",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PlayerStats {
+    pub score: u32,
+    /// Lives left, including the current one.
+    pub lives: u32,
+    pub salvage: u32,
+    pub collisions: u32,
+    pub distance: f32,
+    pub eliminated: Option<EliminationCause>,
+    last_position: Option<Vec3>,
+}
+
+/// Everything recorded about the match in progress. Replaced when play
+/// starts, and turned into a `MatchResult` when it ends.
+pub struct MatchStats {
+    pub players: Vec<PlayerStats>,
+    /// Seconds of play, not counting time spent paused.
+    pub elapsed: f32,
+}
+
+impl PlayerStats {
+    /// Take away a life. Returns whether the player has any left.
+    pub fn lose_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        // The ship comes back somewhere else; don't count the jump.
+        self.last_position = None;
+        self.lives > 0
+    }
+}
+
+impl MatchStats {
+    pub fn new(player_count: usize, lives: u32) -> Self {
+        let player = PlayerStats {
+            lives: lives.max(1),
+            ..Default::default()
+        };
+        Self {
+            players: vec![player; player_count],
+            elapsed: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchOutcome {
+    Winner(usize),
+    /// The players who shared the top score.
+    Tie(Vec<usize>),
+}
+
+/// How a round ended, for the intermission and game over screens.
+pub struct MatchResult {
+    pub outcome: MatchOutcome,
+    pub players: Vec<PlayerStats>,
+    pub duration: f32,
+}
+
+impl MatchResult {
+    /// The highest score wins; equal top scores are a tie. When the last
+    /// ship standing wins, eliminated players can't (unless nobody is left).
+    pub fn from_stats(stats: &MatchStats, rules: &MatchRules) -> Self {
+        let survivors_only = rules.on_elimination == EliminationRule::LastShipStanding
+            && stats.players.iter().any(|p| p.eliminated.is_none());
+        let contenders: Vec<(usize, &PlayerStats)> = stats
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| !survivors_only || p.eliminated.is_none())
+            .collect();
+        let best = contenders.iter().map(|(_, p)| p.score).max().unwrap_or(0);
+        let leaders: Vec<usize> = contenders
+            .iter()
+            .filter(|(_, p)| p.score == best)
+            .map(|(id, _)| *id)
+            .collect();
+        let outcome = if leaders.len() == 1 {
+            MatchOutcome::Winner(leaders[0])
+        } else {
+            MatchOutcome::Tie(leaders)
+        };
+        Self {
+            outcome,
+            players: stats.players.clone(),
+            duration: stats.elapsed,
+        }
+    }
+}
+
+/// Keep the match clock and each player's distance travelled.
+pub fn track_match(
+    time: Res<Time>,
+    mut stats: ResMut<MatchStats>,
+    players: Query<(&Player, &Transform)>,
+) {
+    stats.elapsed += time.delta_seconds();
+    for (player, transform) in players.iter() {
+        if let Some(player_stats) = stats.players.get_mut(player.id) {
+            let position = transform.translation;
+            if let Some(last) = player_stats.last_position {
+                player_stats.distance += last.distance(position);
+            }
+            player_stats.last_position = Some(position);
+        }
+    }
+}
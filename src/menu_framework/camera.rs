@@ -0,0 +1,24 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+
+/// The size of the world the game is laid out in. Whatever the window
+/// size, cameras show the full playfield height.
+pub const PLAYFIELD_WIDTH: f32 = 1024.0;
+pub const PLAYFIELD_HEIGHT: f32 = 768.0;
+
+/// Scale every new 2D camera to fit the playfield into the window, so
+/// changing the window size zooms rather than crops.
+pub fn fit_cameras_to_window(mut cameras: Query<&mut OrthographicProjection, Added<Camera>>) {
+    for mut projection in cameras.iter_mut() {
+        projection.scaling_mode = ScalingMode::FixedVertical;
+        projection.scale = PLAYFIELD_HEIGHT / 2.0;
+    }
+}
+
+/// Convert the cursor position (window coordinates, origin bottom-left)
+/// to world coordinates for a centered camera fitted to the playfield.
+pub(crate) fn cursor_world_position(windows: &Windows) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    let cursor = window.cursor_position()?;
+    let scale = PLAYFIELD_HEIGHT / window.height();
+    Some((cursor - Vec2::new(window.width(), window.height()) / 2.0) * scale)
+}
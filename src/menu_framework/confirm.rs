@@ -0,0 +1,71 @@
+use crate::{
+    centered_text_bundle, screen_depth, spawn_menu, spawn_screen_at_depth, AssetManager, GameMode,
+    Localization, Menu, MenuAction, MenuItem,
+};
+use bevy::prelude::*;
+
+#[derive(Component, Clone)]
+pub struct ConfirmElement;
+
+/// The question the confirmation screen asks, and what to do if the
+/// answer is yes. Set by `MenuAction::Confirm`.
+pub struct ConfirmDialog {
+    /// A string key.
+    pub message: String,
+    pub on_confirm: MenuAction,
+}
+
+pub fn setup_confirm(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    dialog: Option<Res<ConfirmDialog>>,
+    app_state: Res<State<GameMode>>,
+) {
+    let depth = screen_depth(&app_state);
+    spawn_screen_at_depth(
+        &mut commands,
+        &assets,
+        &localization,
+        "confirm",
+        depth,
+        ConfirmElement,
+    );
+
+    let (message, on_confirm) = if let Some(dialog) = dialog {
+        (
+            localization.text(&dialog.message),
+            dialog.on_confirm.clone(),
+        )
+    } else {
+        (localization.text("confirm.default"), MenuAction::Back)
+    };
+    commands
+        .spawn_bundle(centered_text_bundle(
+            &message,
+            assets.font_for(&message),
+            30.0,
+            Color::WHITE,
+            Vec3::new(0.0, 50.0, depth + 2.0),
+        ))
+        .insert(ConfirmElement);
+
+    // "No" comes first, so a stray confirm press is harmless.
+    let menu = Menu::new(vec![
+        MenuItem::new("confirm.no", MenuAction::Back),
+        MenuItem::new("confirm.yes", on_confirm),
+    ])
+    .with_back(MenuAction::Back);
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, -20.0, depth + 2.0),
+        ConfirmElement,
+    );
+}
+
+pub fn exit_confirm(mut commands: Commands) {
+    commands.remove_resource::<ConfirmDialog>();
+}
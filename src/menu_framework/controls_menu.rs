@@ -0,0 +1,237 @@
+use crate::{
+    centered_text_bundle, find_conflict, screen_depth, spawn_menu, spawn_screen_at_depth,
+    AssetManager, Binding, GameMode, GamepadAssignments, InputAction, Localization, Menu,
+    MenuAction, MenuAdjustEvent, MenuEvent, MenuItem, Settings,
+};
+use bevy::prelude::*;
+
+#[derive(Component, Clone)]
+pub struct ControlsElement;
+
+/// The line underneath the menu that prompts for a key and reports
+/// conflicts.
+#[derive(Component)]
+pub struct ControlsStatus;
+
+/// Waiting for a key or button to bind to a player's action.
+pub struct Rebinding {
+    player: usize,
+    action: InputAction,
+}
+
+/// Whose controls the screen is showing. There's room for one player's
+/// actions at a time; the first menu item switches between them.
+pub struct ControlsPlayer(usize);
+
+/// After the player item, the menu has one item per action.
+fn item_action(index: usize) -> InputAction {
+    InputAction::ALL[(index.max(1) - 1) % InputAction::ALL.len()]
+}
+
+fn player_label(settings: &Settings, localization: &Localization, player: usize) -> String {
+    localization.format(
+        "controls.player",
+        &[("name", settings.player_name(player, localization))],
+    )
+}
+
+fn binding_label(
+    settings: &Settings,
+    localization: &Localization,
+    player: usize,
+    action: InputAction,
+) -> String {
+    let mut bindings = settings.players[player].bindings.describe(action);
+    if bindings.is_empty() {
+        bindings = localization.text("controls.unbound");
+    }
+    localization.format(
+        "controls.binding",
+        &[
+            ("action", localization.text(action.name_key())),
+            ("bindings", bindings),
+        ],
+    )
+}
+
+pub fn setup_controls(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    app_state: Res<State<GameMode>>,
+) {
+    let depth = screen_depth(&app_state);
+    spawn_screen_at_depth(
+        &mut commands,
+        &assets,
+        &localization,
+        "controls",
+        depth,
+        ControlsElement,
+    );
+
+    let mut items = vec![MenuItem::new(
+        player_label(&settings, &localization, 0),
+        MenuAction::Custom("player"),
+    )];
+    for action in InputAction::ALL {
+        items.push(MenuItem::new(
+            binding_label(&settings, &localization, 0, action),
+            MenuAction::Custom("rebind"),
+        ));
+    }
+    items.push(MenuItem::new("menu.back", MenuAction::Back));
+    let menu = Menu::new(items).with_back(MenuAction::Back);
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, 220.0, depth + 2.0),
+        ControlsElement,
+    );
+
+    commands
+        .spawn_bundle(centered_text_bundle(
+            "",
+            assets.default_font.clone(),
+            24.0,
+            Color::WHITE,
+            Vec3::new(0.0, -300.0, depth + 2.0),
+        ))
+        .insert(ControlsStatus)
+        .insert(ControlsElement);
+    commands.insert_resource(ControlsPlayer(0));
+}
+
+/// Choosing the player item, or pressing left or right on it, shows the
+/// next or previous player's controls. Choosing a binding waits for the
+/// next key (or button on that player's gamepad, once it has joined), which
+/// replaces the old one. A key that another action already uses is
+/// refused, so two actions can't share a key by accident. Escape cancels.
+/// Axis bindings can only be changed in the settings file.
+#[allow(clippy::too_many_arguments)]
+pub fn update_controls(
+    mut commands: Commands,
+    mut events: EventReader<MenuEvent>,
+    mut adjust_events: EventReader<MenuAdjustEvent>,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    rebinding: Option<Res<Rebinding>>,
+    page: Option<ResMut<ControlsPlayer>>,
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    assignments: Res<GamepadAssignments>,
+    mut settings: ResMut<Settings>,
+    mut menus: Query<&mut Menu, With<ControlsElement>>,
+    mut status: Query<&mut Text, With<ControlsStatus>>,
+) {
+    let (mut menu, mut page) = match (menus.get_single_mut(), page) {
+        (Ok(menu), Some(page)) => (menu, page),
+        _ => return,
+    };
+    let mut set_status = |message: String| {
+        for mut text in status.iter_mut() {
+            text.sections[0].style.font = assets.font_for(&message);
+            text.sections[0].value = message.clone();
+        }
+    };
+
+    if let Some(rebinding) = rebinding {
+        let binding = if keyboard.just_pressed(KeyCode::Escape) {
+            commands.remove_resource::<Rebinding>();
+            set_status(String::new());
+            return;
+        } else if let Some(key) = keyboard.get_just_pressed().next() {
+            Binding::Key(*key)
+        } else if let Some(button) = buttons
+            .get_just_pressed()
+            .find(|button| assignments.gamepad(rebinding.player) == Some(button.0))
+        {
+            Binding::Button(button.1)
+        } else {
+            return;
+        };
+
+        if let Some((player, action)) = find_conflict(
+            settings.players.iter().map(|slot| &slot.bindings),
+            rebinding.player,
+            rebinding.action,
+            binding,
+        ) {
+            set_status(localization.format(
+                "controls.conflict",
+                &[
+                    ("binding", binding.to_string()),
+                    ("player", (player + 1).to_string()),
+                    ("action", localization.text(action.name_key())),
+                ],
+            ));
+        } else {
+            settings.players[rebinding.player]
+                .bindings
+                .rebind(rebinding.action, binding);
+            commands.remove_resource::<Rebinding>();
+            set_status(String::new());
+        }
+    } else if !menu.enabled {
+        // The key that finished rebinding has been released from "just
+        // pressed", so the menu can take input again.
+        menu.enabled = true;
+    } else {
+        let mut rebind = false;
+        let mut delta = 0;
+        for event in events.iter() {
+            match event.action {
+                MenuAction::Custom("rebind") => rebind = true,
+                MenuAction::Custom("player") => delta += 1,
+                _ => {}
+            }
+        }
+        for event in adjust_events.iter() {
+            if event.action == MenuAction::Custom("player") {
+                delta += event.delta;
+            }
+        }
+        if delta != 0 {
+            let count = settings.players.len() as i32;
+            page.0 = (page.0 as i32 + delta).rem_euclid(count) as usize;
+        }
+        if rebind {
+            let (player, action) = (page.0, item_action(menu.selected));
+            commands.insert_resource(Rebinding { player, action });
+            menu.enabled = false;
+            set_status(localization.format(
+                "controls.prompt",
+                &[
+                    ("player", (player + 1).to_string()),
+                    ("action", localization.text(action.name_key())),
+                ],
+            ));
+            // Don't capture the key that opened the prompt.
+            return;
+        }
+    }
+
+    if settings.is_changed() || localization.is_changed() || page.is_changed() {
+        let player = page.0;
+        for (index, item) in menu.items.iter_mut().enumerate() {
+            match item.action {
+                MenuAction::Custom("player") => {
+                    item.label = player_label(&settings, &localization, player);
+                }
+                MenuAction::Custom("rebind") => {
+                    let action = item_action(index);
+                    item.label = binding_label(&settings, &localization, player, action);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn exit_controls(mut commands: Commands) {
+    commands.remove_resource::<Rebinding>();
+    commands.remove_resource::<ControlsPlayer>();
+}
@@ -1,58 +1,260 @@
-use crate::{centered_text_bundle, remove_matching_elements, AssetManager, GameMode};
-use bevy::{ecs::event::Events, prelude::*};
+use crate::{
+    centered_text_bundle, spawn_menu, spawn_screen, AssetManager, GameMode, Localization,
+    MatchOutcome, MatchResult, MatchRules, Menu, MenuAction, MenuItem, PlayerStats, Series,
+    Settings,
+};
+use bevy::prelude::*;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct GameOverElement;
 
-pub struct GameOverResource {
-    pub message: String,
+#[derive(Component, Clone)]
+pub struct IntermissionElement;
+
+const RESULTS_TOP: f32 = 150.0;
+const ROW_HEIGHT: f32 = 30.0;
+
+/// Who won a round (or, with `winner_key`, the whole match).
+fn headline(
+    result: &MatchResult,
+    settings: &Settings,
+    localization: &Localization,
+    winner_key: &str,
+) -> String {
+    match &result.outcome {
+        MatchOutcome::Winner(id) => localization.format(
+            winner_key,
+            &[("name", settings.player_name(*id, localization))],
+        ),
+        MatchOutcome::Tie(ids) => {
+            let names: Vec<String> = ids
+                .iter()
+                .map(|id| settings.player_name(*id, localization))
+                .collect();
+            localization.format(
+                "game_over.tie",
+                &[(
+                    "players",
+                    names.join(&localization.text("game_over.tie_separator")),
+                )],
+            )
+        }
+    }
 }
 
-pub fn setup_game_over(
-    mut commands: Commands,
-    assets: Res<AssetManager>,
-    game_over: Option<Res<GameOverResource>>,
+/// Rounds won by each player, e.g. "Rounds won: Player 1 2   Player 2 1".
+fn standings(series: &Series, settings: &Settings, localization: &Localization) -> String {
+    let players: Vec<String> = series
+        .wins
+        .iter()
+        .enumerate()
+        .map(|(id, wins)| {
+            localization.format(
+                "series.player_wins",
+                &[
+                    ("name", settings.player_name(id, localization)),
+                    ("wins", wins.to_string()),
+                ],
+            )
+        })
+        .collect();
+    localization.format("series.standings", &[("standings", players.join("   "))])
+}
+
+/// The default font is monospaced, so columns line up with padding alone.
+/// Long names are cut short to fit their column.
+fn scoreboard_row(name: &str, stats: &PlayerStats, localization: &Localization) -> String {
+    let fate = stats
+        .eliminated
+        .map_or("game_over.survived", |cause| cause.describe_key());
+    let name: String = name.chars().take(8).collect();
+    format!(
+        "{:<9}{:>6}{:>9}{:>6}{:>10}  {:<10}",
+        name,
+        stats.score,
+        stats.salvage,
+        stats.collisions,
+        format!("{:.0}", stats.distance),
+        localization.text(fate)
+    )
+}
+
+/// Spawn a headline, an optional line under it, and the scoreboard for a
+/// round, from `RESULTS_TOP` down.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_results<T: Component + Clone>(
+    commands: &mut Commands,
+    assets: &AssetManager,
+    settings: &Settings,
+    localization: &Localization,
+    headline: String,
+    subheading: Option<String>,
+    result: &MatchResult,
+    marker: T,
 ) {
-    commands
-        .spawn_bundle(OrthographicCameraBundle::new_2d())
-        .insert(GameOverElement);
+    let mut lines = vec![(headline, 36.0, Color::YELLOW)];
+    if let Some(subheading) = subheading {
+        lines.push((subheading, 20.0, Color::WHITE));
+    }
+    // Leave a gap between the headings and the table.
+    let table_start = lines.len();
+    lines.push((
+        format!(
+            "{:<9}{:>6}{:>9}{:>6}{:>10}  {:<10}",
+            "",
+            localization.text("game_over.score"),
+            localization.text("game_over.salvage"),
+            localization.text("game_over.hits"),
+            localization.text("game_over.distance"),
+            localization.text("game_over.fate")
+        ),
+        20.0,
+        Color::GRAY,
+    ));
+    for (id, stats) in result.players.iter().enumerate() {
+        lines.push((
+            scoreboard_row(&settings.player_name(id, localization), stats, localization),
+            20.0,
+            Color::WHITE,
+        ));
+    }
+    let seconds = result.duration.round() as u32;
+    lines.push((
+        localization.format(
+            "game_over.match_time",
+            &[("time", format!("{}:{:02}", seconds / 60, seconds % 60))],
+        ),
+        20.0,
+        Color::GRAY,
+    ));
 
-    if let Some(game_over) = game_over {
+    for (row, (text, size, color)) in lines.into_iter().enumerate() {
+        let gap = if row >= table_start { ROW_HEIGHT } else { 0.0 };
         commands
             .spawn_bundle(centered_text_bundle(
-                &game_over.message,
-                assets.default_font.clone(),
-                30.0,
-                Color::WHITE,
-                Vec3::new(0.0, 0.0, 2.0),
+                &text,
+                assets.font_for(&text),
+                size,
+                color,
+                Vec3::new(0.0, RESULTS_TOP - row as f32 * ROW_HEIGHT - gap, 2.0),
             ))
-            .insert(GameOverElement {});
+            .insert(marker.clone());
     }
-
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: assets.menu_atlas.clone(),
-            sprite: TextureAtlasSprite::new(1),
-            ..Default::default()
-        })
-        .insert(GameOverElement);
 }
 
-pub fn run_game_over(
-    keyboard: Res<Input<KeyCode>>,
-    mut app_state: ResMut<State<GameMode>>,
-    mut app_exit_events: ResMut<Events<bevy::app::AppExit>>,
+pub fn setup_game_over(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    result: Option<Res<MatchResult>>,
+    series: Option<Res<Series>>,
+    rules: Res<MatchRules>,
 ) {
-    if keyboard.pressed(KeyCode::Return) {
-        app_state
-            .set(GameMode::MainMenu)
-            .expect("Failed to change mode");
-    }
-    if keyboard.pressed(KeyCode::Q) {
-        app_exit_events.send(bevy::app::AppExit);
+    spawn_screen(
+        &mut commands,
+        &assets,
+        &localization,
+        "game_over",
+        GameOverElement,
+    );
+
+    if let Some(result) = result {
+        // A match of several rounds is summed up by who took the series;
+        // the table shows the deciding round.
+        let series = series.filter(|_| rules.rounds_to_win > 1);
+        let (headline, subheading) = match (series.as_ref(), &result.outcome) {
+            (Some(series), MatchOutcome::Winner(_)) => (
+                headline(&result, &settings, &localization, "series.winner"),
+                Some(standings(series, &settings, &localization)),
+            ),
+            _ => (
+                headline(&result, &settings, &localization, "game_over.winner"),
+                None,
+            ),
+        };
+        spawn_results(
+            &mut commands,
+            &assets,
+            &settings,
+            &localization,
+            headline,
+            subheading,
+            &result,
+            GameOverElement,
+        );
     }
+
+    let menu = Menu::new(vec![
+        MenuItem::new(
+            "game_over.rematch",
+            MenuAction::ChangeMode(GameMode::Playing),
+        )
+        .with_hotkey(KeyCode::R),
+        MenuItem::new("menu.main_menu", MenuAction::ChangeMode(GameMode::MainMenu)),
+        MenuItem::new("menu.quit", MenuAction::Quit).with_hotkey(KeyCode::Q),
+    ])
+    .with_back(MenuAction::ChangeMode(GameMode::MainMenu));
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, -150.0, 3.0),
+        GameOverElement,
+    );
 }
 
-pub fn exit_game_over(elements: Query<(Entity, &GameOverElement)>, mut commands: Commands) {
-    remove_matching_elements(elements, &mut commands);
+/// Between the rounds of a match: who won the round, the standings, and
+/// the round's scoreboard.
+pub fn setup_intermission(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    result: Option<Res<MatchResult>>,
+    series: Option<Res<Series>>,
+) {
+    spawn_screen(
+        &mut commands,
+        &assets,
+        &localization,
+        "intermission",
+        IntermissionElement,
+    );
+
+    if let Some(result) = result {
+        spawn_results(
+            &mut commands,
+            &assets,
+            &settings,
+            &localization,
+            headline(&result, &settings, &localization, "intermission.winner"),
+            series.map(|series| standings(&series, &settings, &localization)),
+            &result,
+            IntermissionElement,
+        );
+    }
+
+    let menu = Menu::new(vec![
+        MenuItem::new(
+            "intermission.next_round",
+            MenuAction::ChangeMode(GameMode::Playing),
+        ),
+        MenuItem::new(
+            "menu.main_menu",
+            MenuAction::Confirm(
+                "pause.confirm_abandon".to_string(),
+                Box::new(MenuAction::ChangeMode(GameMode::MainMenu)),
+            ),
+        ),
+    ]);
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, -150.0, 3.0),
+        IntermissionElement,
+    );
 }
@@ -0,0 +1,53 @@
+use crate::{ActionInput, GameMode};
+use bevy::{prelude::*, utils::HashMap};
+
+/// How long each screen must be shown before it accepts input, set with
+/// `Screen::with_min_display`.
+#[derive(Default)]
+pub struct MinDisplayTimes(pub HashMap<GameMode, f32>);
+
+/// Keeps input from leaking from one screen into the next. Whenever the
+/// screen changes, anything held is ignored until it's pressed again, and
+/// the new screen can insist on being seen for a moment (so a button
+/// mashed at the end of a match doesn't skip the results).
+#[derive(Default)]
+pub struct InputGuard {
+    /// The screen on top of the stack, and how many are beneath it.
+    screen: Option<(GameMode, usize)>,
+    shown_for: f32,
+    min_display: f32,
+}
+
+impl InputGuard {
+    /// Has the current screen been up long enough to take input?
+    pub fn accepts_input(&self) -> bool {
+        self.shown_for >= self.min_display
+    }
+}
+
+pub fn guard_screen_changes(
+    app_state: Res<State<GameMode>>,
+    time: Res<Time>,
+    min_display: Res<MinDisplayTimes>,
+    mut guard: ResMut<InputGuard>,
+    mut actions: ResMut<ActionInput>,
+) {
+    // The state resource is touched every frame by Bevy's state driver, so
+    // change detection can't be used to spot a new screen.
+    let screen = Some((*app_state.current(), app_state.inactives().len()));
+    if guard.screen != screen {
+        guard.screen = screen;
+        guard.shown_for = 0.0;
+        guard.min_display = min_display
+            .0
+            .get(app_state.current())
+            .copied()
+            .unwrap_or(0.0);
+        actions.require_fresh_press();
+    } else {
+        guard.shown_for += time.delta_seconds();
+    }
+    if !guard.accepts_input() {
+        actions.set_blocked(true);
+    }
+}
@@ -1,38 +1,98 @@
-use crate::{remove_matching_elements, AssetManager, GameMode};
-use bevy::{ecs::event::Events, prelude::*};
+use crate::{
+    centered_text_bundle, spawn_menu, spawn_screen, AssetManager, GameMode, GamepadAssignments,
+    Localization, Menu, MenuAction, MenuItem, Series, Settings,
+};
+use bevy::prelude::*;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct MainMenuElement;
 
-pub fn setup_main_menu(mut commands: Commands, assets: Res<AssetManager>) {
-    commands
-        .spawn_bundle(OrthographicCameraBundle::new_2d())
-        .insert(MainMenuElement);
+/// Lists which players have a gamepad, and which are bots.
+#[derive(Component)]
+pub struct JoinStatus;
+
+pub fn setup_main_menu(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+) {
+    // Whatever match was going on has been abandoned.
+    commands.remove_resource::<Series>();
+
+    spawn_screen(
+        &mut commands,
+        &assets,
+        &localization,
+        "main_menu",
+        MainMenuElement,
+    );
+
+    let menu = Menu::new(vec![
+        MenuItem::new("menu.play", MenuAction::ChangeMode(GameMode::Playing))
+            .with_hotkey(KeyCode::P),
+        MenuItem::new("menu.options", MenuAction::OpenScreen(GameMode::Options))
+            .with_hotkey(KeyCode::O),
+        MenuItem::new("menu.quit", MenuAction::Quit).with_hotkey(KeyCode::Q),
+    ])
+    .with_back(MenuAction::Confirm(
+        "pause.confirm_quit".to_string(),
+        Box::new(MenuAction::Quit),
+    ));
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, -150.0, 1.0),
+        MainMenuElement,
+    );
 
     commands
-        .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: assets.menu_atlas.clone(),
-            sprite: TextureAtlasSprite::new(0),
-            ..Default::default()
-        })
+        .spawn_bundle(centered_text_bundle(
+            "",
+            assets.default_font.clone(),
+            20.0,
+            Color::GRAY,
+            Vec3::new(0.0, -330.0, 1.0),
+        ))
+        .insert(JoinStatus)
         .insert(MainMenuElement);
 }
 
-pub fn run_main_menu(
-    keyboard: Res<Input<KeyCode>>,
-    mut app_state: ResMut<State<GameMode>>,
-    mut app_exit_events: ResMut<Events<bevy::app::AppExit>>,
+pub fn show_join_status(
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    gamepads: Res<Gamepads>,
+    assignments: Res<GamepadAssignments>,
+    mut status: Query<&mut Text, With<JoinStatus>>,
 ) {
-    if keyboard.pressed(KeyCode::P) {
-        app_state
-            .set(GameMode::Playing)
-            .expect("Failed to change mode");
-    }
-    if keyboard.pressed(KeyCode::Q) {
-        app_exit_events.send(bevy::app::AppExit);
+    let players: Vec<String> = (0..settings.player_count)
+        .map(|player| {
+            let device = match assignments.gamepad(player) {
+                _ if settings.players[player].bot.is_some() => "join.bot",
+                Some(pad) if gamepads.contains(&pad) => "join.gamepad",
+                Some(_) => "join.disconnected",
+                None => "join.keyboard",
+            };
+            localization.format(
+                "join.player",
+                &[
+                    ("player", (player + 1).to_string()),
+                    ("device", localization.text(device)),
+                ],
+            )
+        })
+        .collect();
+    let message = format!(
+        "{}\n{}",
+        players.join("   "),
+        localization.text("join.prompt")
+    );
+    for mut text in status.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].style.font = assets.font_for(&message);
+            text.sections[0].value = message.clone();
+        }
     }
 }
-
-pub fn exit_main_menu(elements: Query<(Entity, &MainMenuElement)>, mut commands: Commands) {
-    remove_matching_elements(elements, &mut commands);
-}
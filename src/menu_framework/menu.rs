@@ -0,0 +1,323 @@
+use super::camera::cursor_world_position;
+use crate::{
+    centered_text_bundle, ActionInput, AssetManager, ConfirmDialog, CrossFading, GameMode,
+    InputAction, InputGuard, Localization, ScreenCommand, ScreenTransition,
+};
+use bevy::{app::AppExit, prelude::*, text::Text2dSize};
+
+const MENU_FONT_SIZE: f32 = 30.0;
+const MENU_LINE_HEIGHT: f32 = 45.0;
+const NORMAL_COLOR: Color = Color::GRAY;
+const SELECTED_COLOR: Color = Color::YELLOW;
+
+/// What happens when a menu item is chosen.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuAction {
+    /// Leave this screen, and any screens beneath it, for another game
+    /// mode. Changing to the mode beneath an overlay restarts it.
+    ChangeMode(GameMode),
+    /// Open a sub-screen on top of this one.
+    OpenScreen(GameMode),
+    /// Close this screen, returning to the one underneath.
+    Back,
+    /// Exit the program.
+    Quit,
+    /// Ask "are you sure?" on a confirmation screen, then take the action.
+    /// The question is a string key.
+    Confirm(String, Box<MenuAction>),
+    /// Handled by the screen that owns the menu, by reading `MenuEvent`s.
+    Custom(&'static str),
+}
+
+#[derive(Clone)]
+pub struct MenuItem {
+    /// A string key, shown in the current language. Labels that aren't
+    /// keys (ones a screen has already formatted) are shown as they are.
+    pub label: String,
+    pub action: MenuAction,
+    pub hotkey: Option<KeyCode>,
+}
+
+impl MenuItem {
+    pub fn new<S: ToString>(label: S, action: MenuAction) -> Self {
+        Self {
+            label: label.to_string(),
+            action,
+            hotkey: None,
+        }
+    }
+
+    /// A key that activates this item directly, without selecting it first.
+    pub fn with_hotkey(mut self, key: KeyCode) -> Self {
+        self.hotkey = Some(key);
+        self
+    }
+}
+
+/// A vertical list of selectable items. Navigate with the arrow keys,
+/// W/S, any gamepad's D-pad or the mouse; confirm with any player's
+/// `Confirm` action, a gamepad's South button or a click. Escape (or the East button) triggers
+/// the `back` action, if there is one. Left and right (A/D, or the D-pad)
+/// send a `MenuAdjustEvent` for the selected item.
+///
+/// Only the front-most menu (highest z) responds to input, so a menu on
+/// an overlay screen takes over from the one beneath it. Screens can also
+/// set `enabled` to false while they handle input themselves.
+#[derive(Component)]
+pub struct Menu {
+    pub items: Vec<MenuItem>,
+    pub selected: usize,
+    pub back: Option<MenuAction>,
+    pub enabled: bool,
+    position: Vec3,
+}
+
+impl Menu {
+    pub fn new(items: Vec<MenuItem>) -> Self {
+        Self {
+            items,
+            selected: 0,
+            back: None,
+            enabled: true,
+            position: Vec3::ZERO,
+        }
+    }
+
+    /// The action to take when "back" is pressed.
+    pub fn with_back(mut self, action: MenuAction) -> Self {
+        self.back = Some(action);
+        self
+    }
+
+    fn item_position(&self, index: usize) -> Vec3 {
+        self.position - Vec3::new(0.0, index as f32 * MENU_LINE_HEIGHT, 0.0)
+    }
+
+    /// Is `point` on an item's label, measured as laid out in whatever
+    /// font it's drawn with?
+    fn item_contains(&self, index: usize, point: Vec2, label_size: Vec2) -> bool {
+        let center = self.item_position(index);
+        let half_width = label_size.x / 2.0;
+        let half_height = MENU_LINE_HEIGHT / 2.0;
+        (point.x - center.x).abs() < half_width && (point.y - center.y).abs() < half_height
+    }
+}
+
+/// Tags the text entity displaying a menu item.
+#[derive(Component)]
+pub struct MenuItemText {
+    menu: Entity,
+    index: usize,
+}
+
+/// Sent when a menu item is activated. Built-in actions are handled by the
+/// menu framework; screens read these events for their `Custom` actions.
+pub struct MenuEvent {
+    pub action: MenuAction,
+}
+
+/// Sent when left or right is pressed on a menu item with a `Custom`
+/// action, so screens can offer adjustable values (volume, for example).
+/// `delta` is -1 for left and 1 for right.
+pub struct MenuAdjustEvent {
+    pub action: MenuAction,
+    pub delta: i32,
+}
+
+/// Spawn a menu with its first item centered on `position`, tagging every
+/// entity with `marker` so the screen's exit system can clean it up.
+pub fn spawn_menu<T: Component + Clone>(
+    commands: &mut Commands,
+    assets: &AssetManager,
+    localization: &Localization,
+    mut menu: Menu,
+    position: Vec3,
+    marker: T,
+) -> Entity {
+    menu.position = position;
+    let labels: Vec<(String, Vec3)> = menu
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (localization.text(&item.label), menu.item_position(i)))
+        .collect();
+    let menu_entity = commands.spawn().insert(menu).insert(marker.clone()).id();
+
+    for (index, (label, position)) in labels.into_iter().enumerate() {
+        commands
+            .spawn_bundle(centered_text_bundle(
+                &label,
+                assets.font_for(&label),
+                MENU_FONT_SIZE,
+                NORMAL_COLOR,
+                position,
+            ))
+            .insert(MenuItemText {
+                menu: menu_entity,
+                index,
+            })
+            .insert(marker.clone());
+    }
+    menu_entity
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn menu_navigation(
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    mouse: Res<Input<MouseButton>>,
+    actions: Res<ActionInput>,
+    transition: Res<ScreenTransition>,
+    guard: Res<InputGuard>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    windows: Res<Windows>,
+    mut menus: Query<(Entity, &mut Menu)>,
+    labels: Query<(&MenuItemText, &Text2dSize)>,
+    mut events: EventWriter<MenuEvent>,
+    mut adjust_events: EventWriter<MenuAdjustEvent>,
+) {
+    if transition.is_active() || !guard.accepts_input() {
+        return;
+    }
+    let (menu_entity, mut menu) = if let Some(menu) = menus
+        .iter_mut()
+        .max_by(|(_, a), (_, b)| a.position.z.total_cmp(&b.position.z))
+    {
+        menu
+    } else {
+        return;
+    };
+    if menu.items.is_empty() || !menu.enabled {
+        return;
+    }
+
+    let pad_pressed = |button| {
+        gamepads
+            .iter()
+            .any(|pad| buttons.just_pressed(GamepadButton(*pad, button)))
+    };
+    let up = keyboard.any_just_pressed([KeyCode::Up, KeyCode::W])
+        || pad_pressed(GamepadButtonType::DPadUp);
+    let down = keyboard.any_just_pressed([KeyCode::Down, KeyCode::S])
+        || pad_pressed(GamepadButtonType::DPadDown);
+    let mut confirm =
+        actions.any_just_pressed(InputAction::Confirm) || pad_pressed(GamepadButtonType::South);
+    let back = keyboard.just_pressed(KeyCode::Escape) || pad_pressed(GamepadButtonType::East);
+    let left = keyboard.any_just_pressed([KeyCode::Left, KeyCode::A])
+        || pad_pressed(GamepadButtonType::DPadLeft);
+    let right = keyboard.any_just_pressed([KeyCode::Right, KeyCode::D])
+        || pad_pressed(GamepadButtonType::DPadRight);
+
+    let count = menu.items.len();
+    if up {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+    if down {
+        menu.selected = (menu.selected + 1) % count;
+    }
+
+    // The mouse selects whatever it moves over, and clicking confirms it.
+    let cursor_moved = cursor_moved.iter().count() > 0;
+    let clicked = mouse.just_pressed(MouseButton::Left);
+    if cursor_moved || clicked {
+        if let Some(cursor) = cursor_world_position(&windows) {
+            let hovered = labels
+                .iter()
+                .filter(|(label, _)| label.menu == menu_entity)
+                .find(|(label, size)| {
+                    let size = Vec2::new(size.size.width, size.size.height);
+                    menu.item_contains(label.index, cursor, size)
+                })
+                .map(|(label, _)| label.index);
+            if let Some(hovered) = hovered {
+                menu.selected = hovered;
+                confirm |= clicked;
+            }
+        }
+    }
+
+    let selected = &menu.items[menu.selected];
+    if matches!(selected.action, MenuAction::Custom(_)) && left != right {
+        adjust_events.send(MenuAdjustEvent {
+            action: selected.action.clone(),
+            delta: if left { -1 } else { 1 },
+        });
+    }
+
+    if let Some(item) = menu
+        .items
+        .iter()
+        .find(|item| item.hotkey.is_some_and(|key| keyboard.just_pressed(key)))
+    {
+        events.send(MenuEvent {
+            action: item.action.clone(),
+        });
+    } else if confirm {
+        events.send(MenuEvent {
+            action: menu.items[menu.selected].action.clone(),
+        });
+    } else if back {
+        if let Some(action) = &menu.back {
+            events.send(MenuEvent {
+                action: action.clone(),
+            });
+        }
+    }
+}
+
+/// Color each item's text to show which one is selected, and keep it in
+/// step with the item's label and the language, which may change.
+pub fn menu_highlight(
+    menus: Query<&Menu>,
+    assets: Option<Res<AssetManager>>,
+    localization: Option<Res<Localization>>,
+    mut texts: Query<(&MenuItemText, &mut Text), Without<CrossFading>>,
+) {
+    let (assets, localization) = match (assets, localization) {
+        (Some(assets), Some(localization)) => (assets, localization),
+        _ => return,
+    };
+    for (item, mut text) in texts.iter_mut() {
+        if let Ok(menu) = menus.get(item.menu) {
+            let label = localization.text(&menu.items[item.index].label);
+            if text.sections[0].value != label {
+                text.sections[0].style.font = assets.font_for(&label);
+                text.sections[0].value = label;
+            }
+            let color = if menu.selected == item.index {
+                SELECTED_COLOR
+            } else {
+                NORMAL_COLOR
+            };
+            for section in text.sections.iter_mut() {
+                section.style.color = color;
+            }
+        }
+    }
+}
+
+/// Carry out the built-in menu actions.
+pub fn apply_menu_actions(
+    mut commands: Commands,
+    mut events: EventReader<MenuEvent>,
+    mut screen_commands: EventWriter<ScreenCommand>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    for event in events.iter() {
+        match &event.action {
+            MenuAction::ChangeMode(mode) => screen_commands.send(ScreenCommand::Replace(*mode)),
+            MenuAction::OpenScreen(mode) => screen_commands.send(ScreenCommand::Push(*mode)),
+            MenuAction::Back => screen_commands.send(ScreenCommand::Pop),
+            MenuAction::Quit => app_exit_events.send(AppExit),
+            MenuAction::Confirm(message, on_confirm) => {
+                commands.insert_resource(ConfirmDialog {
+                    message: message.clone(),
+                    on_confirm: (**on_confirm).clone(),
+                });
+                screen_commands.send(ScreenCommand::Push(GameMode::Confirm));
+            }
+            MenuAction::Custom(_) => {}
+        }
+    }
+}
@@ -1,40 +1,153 @@
-/// The menu framework is another item I reuse. It just displays graphics
-/// from assets/menus.png to provide a loading, menu, and game over
-/// screen foundation. If a game is good, I remove this and use
-/// something tailored to the game.
+//! The menu framework is another item I reuse. It displays the screen
+//! backgrounds registered with the asset plugin to provide a loading,
+//! menu, and game over screen foundation. If a game is good, I remove
+//! this and use something tailored to the game.
 
+mod camera;
+mod confirm;
+mod controls_menu;
 mod game_over;
+mod input_guard;
 mod main_menu;
+mod menu;
+mod options;
+mod pause;
 mod play_game;
+mod players_menu;
+mod rules_menu;
+mod screen_stack;
+mod screens;
+mod transition;
+use crate::ActionInputSystem;
 use bevy::prelude::*;
+pub use camera::*;
+pub use confirm::*;
+pub use controls_menu::*;
 pub use game_over::*;
+pub use input_guard::*;
 pub use main_menu::*;
+pub use menu::*;
+pub use options::*;
+pub use pause::*;
 pub use play_game::*;
+pub use players_menu::*;
+pub use rules_menu::*;
+pub use screen_stack::*;
+pub use screens::*;
+pub use transition::*;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Copy)]
 pub enum GameMode {
     Loading,
     MainMenu,
     Playing,
+    Paused,
+    Options,
+    Players,
+    Controls,
+    Rules,
+    Confirm,
+    Intermission,
     GameOver,
 }
 
 /// Creates a basic 1024x768 Bevy game structure, featuring a Loading
-/// screen, a Main Menu, and a Game Over menu.
+/// screen, a Main Menu, a Pause overlay, Options, Players, Controls and
+/// Rules screens, a confirmation dialog, an Intermission between rounds, and a
+/// Game Over menu. Screens are kept on a
+/// stack, so overlays can be pushed on top of the screen beneath them,
+/// and share a single camera. Replacing the stack fades between screens.
 pub struct BasicGamePlugin;
 
 impl Plugin for BasicGamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state(GameMode::Loading)
-            // Main Menu Handler
-            .add_system_set(SystemSet::on_enter(GameMode::MainMenu).with_system(setup_main_menu))
-            .add_system_set(SystemSet::on_update(GameMode::MainMenu).with_system(run_main_menu))
-            .add_system_set(SystemSet::on_exit(GameMode::MainMenu).with_system(exit_main_menu))
-            // Play Game Handler
-            .add_system_set(SystemSet::on_exit(GameMode::Playing).with_system(exit_play_game))
-            // Game Over Handler
-            .add_system_set(SystemSet::on_enter(GameMode::GameOver).with_system(setup_game_over))
-            .add_system_set(SystemSet::on_update(GameMode::GameOver).with_system(run_game_over))
-            .add_system_set(SystemSet::on_exit(GameMode::GameOver).with_system(exit_game_over));
+        app.init_resource::<TransitionSettings>()
+            .init_resource::<ScreenTransition>()
+            .init_resource::<InputGuard>()
+            .init_resource::<MinDisplayTimes>()
+            .add_state(GameMode::Loading)
+            .add_event::<MenuEvent>()
+            .add_event::<MenuAdjustEvent>()
+            .add_event::<ScreenCommand>()
+            // Menu widgets, on whichever screen is showing them
+            .add_system(menu_navigation)
+            .add_system(menu_highlight)
+            .add_system(apply_menu_actions)
+            .add_system(apply_screen_commands)
+            .add_system(fit_cameras_to_window)
+            .add_startup_system(setup_camera)
+            .add_system(run_transitions)
+            .add_system(update_localized_text)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                block_input_during_transitions
+                    .label("block_input_during_transitions")
+                    .after(ActionInputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                guard_screen_changes.after("block_input_during_transitions"),
+            )
+            .add_screen(
+                Screen::new(GameMode::MainMenu)
+                    .on_enter(setup_main_menu)
+                    .on_update(show_join_status)
+                    .with_cleanup::<MainMenuElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Playing)
+                    .on_update(pause_game)
+                    .with_cleanup::<PlayGameElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Paused)
+                    .on_enter(setup_pause)
+                    .with_cleanup::<PauseElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Options)
+                    .on_enter(setup_options)
+                    .on_update(update_options)
+                    .on_exit(exit_options)
+                    .with_cleanup::<OptionsElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Players)
+                    .on_enter(setup_players)
+                    .on_update(update_players)
+                    .on_exit(exit_players)
+                    .with_cleanup::<PlayersElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Controls)
+                    .on_enter(setup_controls)
+                    .on_update(update_controls)
+                    .on_exit(exit_controls)
+                    .with_cleanup::<ControlsElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Rules)
+                    .on_enter(setup_rules)
+                    .on_update(update_rules)
+                    .with_cleanup::<RulesElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Confirm)
+                    .on_enter(setup_confirm)
+                    .on_exit(exit_confirm)
+                    .with_cleanup::<ConfirmElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::Intermission)
+                    .on_enter(setup_intermission)
+                    .with_min_display(1.5)
+                    .with_cleanup::<IntermissionElement>(),
+            )
+            .add_screen(
+                Screen::new(GameMode::GameOver)
+                    .on_enter(setup_game_over)
+                    .with_min_display(1.5)
+                    .with_cleanup::<GameOverElement>(),
+            );
     }
 }
@@ -0,0 +1,172 @@
+use crate::{
+    screen_depth, spawn_menu, spawn_screen_at_depth, AssetManager, GameMode, Localization, Menu,
+    MenuAction, MenuAdjustEvent, MenuEvent, MenuItem, Settings, WINDOW_SIZES,
+};
+use bevy::prelude::*;
+
+#[derive(Component, Clone)]
+pub struct OptionsElement;
+
+/// The adjustable options, by their `MenuAction::Custom` id.
+const OPTIONS: [&str; 4] = ["language", "window", "particles", "deadzone"];
+
+fn option_label(option: &str, settings: &Settings, localization: &Localization) -> String {
+    let percent = |value: f32| format!("{:.0}", value * 100.0);
+    match option {
+        "language" => localization.format(
+            "options.language",
+            &[(
+                "language",
+                localization.language_name(&settings.language).to_string(),
+            )],
+        ),
+        "window" => localization.format(
+            "options.window",
+            &[
+                ("width", settings.window_width.to_string()),
+                ("height", settings.window_height.to_string()),
+            ],
+        ),
+        "particles" => localization.format(
+            "options.particles",
+            &[("percent", percent(settings.particle_density))],
+        ),
+        "deadzone" => localization.format(
+            "options.deadzone",
+            &[("percent", percent(settings.gamepad_deadzone))],
+        ),
+        _ => option.to_string(),
+    }
+}
+
+/// Step an option forwards or backwards through its values.
+fn adjust_option(option: &str, delta: i32, settings: &mut Settings, localization: &Localization) {
+    match option {
+        "language" => {
+            let languages = &localization.languages;
+            if !languages.is_empty() {
+                let current = languages
+                    .iter()
+                    .position(|language| language.code == settings.language)
+                    .unwrap_or(0);
+                settings.language = languages[step(current, delta, languages.len())]
+                    .code
+                    .clone();
+            }
+        }
+        "window" => {
+            let current = WINDOW_SIZES
+                .iter()
+                .position(|(w, h)| *w == settings.window_width && *h == settings.window_height)
+                .unwrap_or(1);
+            let (width, height) = WINDOW_SIZES[step(current, delta, WINDOW_SIZES.len())];
+            settings.window_width = width;
+            settings.window_height = height;
+        }
+        "particles" => {
+            settings.particle_density =
+                (settings.particle_density + delta as f32 * 0.25).clamp(0.25, 2.0);
+        }
+        "deadzone" => {
+            settings.gamepad_deadzone =
+                (settings.gamepad_deadzone + delta as f32 * 0.05).clamp(0.0, 0.5);
+        }
+        _ => {}
+    }
+}
+
+/// Move `delta` places through a list of `len` values, wrapping around.
+fn step(current: usize, delta: i32, len: usize) -> usize {
+    (current as i32 + delta).rem_euclid(len as i32) as usize
+}
+
+pub fn setup_options(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    app_state: Res<State<GameMode>>,
+) {
+    let depth = screen_depth(&app_state);
+    spawn_screen_at_depth(
+        &mut commands,
+        &assets,
+        &localization,
+        "options",
+        depth,
+        OptionsElement,
+    );
+
+    let mut items: Vec<MenuItem> = OPTIONS
+        .iter()
+        .map(|option| {
+            MenuItem::new(
+                option_label(option, &settings, &localization),
+                MenuAction::Custom(option),
+            )
+        })
+        .collect();
+    items.push(MenuItem::new(
+        "options.players",
+        MenuAction::OpenScreen(GameMode::Players),
+    ));
+    items.push(MenuItem::new(
+        "options.controls",
+        MenuAction::OpenScreen(GameMode::Controls),
+    ));
+    items.push(MenuItem::new(
+        "options.rules",
+        MenuAction::OpenScreen(GameMode::Rules),
+    ));
+    items.push(MenuItem::new("menu.back", MenuAction::Back));
+    let menu = Menu::new(items).with_back(MenuAction::Back);
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, 130.0, depth + 2.0),
+        OptionsElement,
+    );
+}
+
+/// Choosing an option steps it forwards; left and right step it either way.
+/// Changes are applied as soon as they're made.
+pub fn update_options(
+    mut events: EventReader<MenuEvent>,
+    mut adjust_events: EventReader<MenuAdjustEvent>,
+    mut settings: ResMut<Settings>,
+    localization: Res<Localization>,
+    mut menus: Query<&mut Menu, With<OptionsElement>>,
+) {
+    let adjustments = events
+        .iter()
+        .map(|event| (event.action.clone(), 1))
+        .chain(
+            adjust_events
+                .iter()
+                .map(|event| (event.action.clone(), event.delta)),
+        )
+        .collect::<Vec<_>>();
+    for (action, delta) in adjustments {
+        if let MenuAction::Custom(option) = action {
+            if OPTIONS.contains(&option) {
+                adjust_option(option, delta, &mut settings, &localization);
+            }
+        }
+    }
+
+    if settings.is_changed() || localization.is_changed() {
+        for mut menu in menus.iter_mut() {
+            for item in menu.items.iter_mut() {
+                if let MenuAction::Custom(option) = item.action {
+                    item.label = option_label(option, &settings, &localization);
+                }
+            }
+        }
+    }
+}
+
+pub fn exit_options(settings: Res<Settings>) {
+    settings.save();
+}
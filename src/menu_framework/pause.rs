@@ -0,0 +1,90 @@
+use crate::{
+    screen_depth, spawn_menu, spawn_screen_at_depth, AssetManager, GameMode, GamepadAssignments,
+    InputGuard, Localization, Menu, MenuAction, MenuItem, ScreenCommand, ScreenTransition,
+};
+use bevy::{input::gamepad::GamepadEventType, prelude::*, window::WindowFocused};
+
+#[derive(Component, Clone)]
+pub struct PauseElement;
+
+/// Pause with Escape or a player's Start button, or automatically when
+/// the window loses focus or a player's gamepad disconnects. Pausing
+/// pushes `GameMode::Paused` on top of `GameMode::Playing`, so the game's
+/// systems stop running but its entities are kept.
+#[allow(clippy::too_many_arguments)]
+pub fn pause_game(
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    assignments: Res<GamepadAssignments>,
+    buttons: Res<Input<GamepadButton>>,
+    mut focus_events: EventReader<WindowFocused>,
+    mut gamepad_events: EventReader<GamepadEvent>,
+    transition: Res<ScreenTransition>,
+    guard: Res<InputGuard>,
+    mut screen_commands: EventWriter<ScreenCommand>,
+) {
+    let lost_focus = focus_events.iter().any(|event| !event.focused);
+    let start_pressed = gamepads.iter().any(|pad| {
+        assignments.player(*pad).is_some()
+            && !assignments.just_joined(*pad)
+            && buttons.just_pressed(GamepadButton(*pad, GamepadButtonType::Start))
+    });
+    let lost_gamepad = gamepad_events.iter().any(|GamepadEvent(pad, event)| {
+        *event == GamepadEventType::Disconnected && assignments.player(*pad).is_some()
+    });
+
+    let wants_pause =
+        keyboard.just_pressed(KeyCode::Escape) || start_pressed || lost_focus || lost_gamepad;
+    if wants_pause && !transition.is_active() && guard.accepts_input() {
+        screen_commands.send(ScreenCommand::Push(GameMode::Paused));
+    }
+}
+
+pub fn setup_pause(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    app_state: Res<State<GameMode>>,
+) {
+    let depth = screen_depth(&app_state);
+    spawn_screen_at_depth(
+        &mut commands,
+        &assets,
+        &localization,
+        "pause",
+        depth,
+        PauseElement,
+    );
+
+    let menu = Menu::new(vec![
+        MenuItem::new("pause.resume", MenuAction::Back),
+        MenuItem::new(
+            "pause.restart",
+            MenuAction::Confirm(
+                "pause.confirm_restart".to_string(),
+                Box::new(MenuAction::ChangeMode(GameMode::Playing)),
+            ),
+        ),
+        MenuItem::new("menu.options", MenuAction::OpenScreen(GameMode::Options)),
+        MenuItem::new(
+            "menu.main_menu",
+            MenuAction::Confirm(
+                "pause.confirm_abandon".to_string(),
+                Box::new(MenuAction::ChangeMode(GameMode::MainMenu)),
+            ),
+        ),
+        MenuItem::new(
+            "menu.quit",
+            MenuAction::Confirm("pause.confirm_quit".to_string(), Box::new(MenuAction::Quit)),
+        ),
+    ])
+    .with_back(MenuAction::Back);
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, 40.0, depth + 2.0),
+        PauseElement,
+    );
+}
@@ -1,10 +1,6 @@
 use bevy::prelude::*;
 
-use crate::remove_matching_elements;
-
+/// Tags every entity belonging to a match, so that leaving
+/// `GameMode::Playing` removes them.
 #[derive(Component)]
 pub struct PlayGameElement;
-
-pub fn exit_play_game(elements: Query<(Entity, &PlayGameElement)>, mut commands: Commands) {
-    remove_matching_elements(elements, &mut commands);
-}
@@ -0,0 +1,187 @@
+use crate::{
+    color_key, screen_depth, ship_key, spawn_menu, spawn_screen_at_depth, AssetManager,
+    BotDifficulty, GameMode, Localization, Menu, MenuAction, MenuAdjustEvent, MenuEvent, MenuItem,
+    Settings, MAX_PLAYERS, PLAYER_COLORS, SHIP_DESIGNS,
+};
+use bevy::prelude::*;
+
+#[derive(Component, Clone)]
+pub struct PlayersElement;
+
+/// The player slot the screen is showing. Like the controls screen, one
+/// slot is shown at a time, and the "slot" item switches between them.
+pub struct PlayersPage(usize);
+
+/// The adjustable settings, by their `MenuAction::Custom` id: the player
+/// count, which slot to show, and that slot's colour, ship and controller.
+const PLAYER_OPTIONS: [&str; 5] = ["count", "slot", "color", "ship", "control"];
+
+/// Who can play a slot: a person, or a bot of some difficulty.
+const CONTROLLERS: [Option<BotDifficulty>; 4] = [
+    None,
+    Some(BotDifficulty::Easy),
+    Some(BotDifficulty::Normal),
+    Some(BotDifficulty::Hard),
+];
+
+fn option_label(
+    option: &str,
+    slot: usize,
+    settings: &Settings,
+    localization: &Localization,
+) -> String {
+    let player = &settings.players[slot];
+    match option {
+        "count" => localization.format(
+            "players.count",
+            &[("count", settings.player_count.to_string())],
+        ),
+        "slot" => localization.format(
+            "players.slot",
+            &[("name", settings.player_name(slot, localization))],
+        ),
+        "color" => localization.format(
+            "players.color",
+            &[("color", localization.text(color_key(player.color)))],
+        ),
+        "ship" => localization.format(
+            "players.ship",
+            &[("ship", localization.text(ship_key(player.ship)))],
+        ),
+        "control" => localization.format(
+            "players.control",
+            &[(
+                "control",
+                localization.text(player.bot.map_or("control.human", |bot| bot.name_key())),
+            )],
+        ),
+        _ => option.to_string(),
+    }
+}
+
+/// Step a setting forwards or backwards through its values.
+fn adjust_option(option: &str, delta: i32, page: &mut PlayersPage, settings: &mut Settings) {
+    let slot = page.0;
+    match option {
+        "count" => {
+            let current = settings.player_count.clamp(2, MAX_PLAYERS) - 2;
+            settings.player_count = step(current, delta, MAX_PLAYERS - 1) + 2;
+        }
+        "slot" => {
+            page.0 = step(page.0, delta, settings.players.len());
+        }
+        "color" => {
+            let player = &mut settings.players[slot];
+            let current = PLAYER_COLORS
+                .iter()
+                .position(|(_, c)| *c == player.color)
+                .unwrap_or(0);
+            player.color = PLAYER_COLORS[step(current, delta, PLAYER_COLORS.len())].1;
+        }
+        "ship" => {
+            let player = &mut settings.players[slot];
+            player.ship = step(player.ship % SHIP_DESIGNS.len(), delta, SHIP_DESIGNS.len());
+        }
+        "control" => {
+            let player = &mut settings.players[slot];
+            let current = CONTROLLERS
+                .iter()
+                .position(|bot| *bot == player.bot)
+                .unwrap_or(0);
+            player.bot = CONTROLLERS[step(current, delta, CONTROLLERS.len())];
+        }
+        _ => {}
+    }
+}
+
+/// Move `delta` places through a list of `len` values, wrapping around.
+fn step(current: usize, delta: i32, len: usize) -> usize {
+    (current as i32 + delta).rem_euclid(len as i32) as usize
+}
+
+pub fn setup_players(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    app_state: Res<State<GameMode>>,
+) {
+    let depth = screen_depth(&app_state);
+    spawn_screen_at_depth(
+        &mut commands,
+        &assets,
+        &localization,
+        "players",
+        depth,
+        PlayersElement,
+    );
+
+    let mut items: Vec<MenuItem> = PLAYER_OPTIONS
+        .iter()
+        .map(|option| {
+            MenuItem::new(
+                option_label(option, 0, &settings, &localization),
+                MenuAction::Custom(option),
+            )
+        })
+        .collect();
+    items.push(MenuItem::new("menu.back", MenuAction::Back));
+    let menu = Menu::new(items).with_back(MenuAction::Back);
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, 100.0, depth + 2.0),
+        PlayersElement,
+    );
+    commands.insert_resource(PlayersPage(0));
+}
+
+/// Like the options screen: choosing a setting steps it forwards, and
+/// left and right step it either way. The changes apply from the next
+/// match, or for a bot, the next time its ship spawns.
+pub fn update_players(
+    mut events: EventReader<MenuEvent>,
+    mut adjust_events: EventReader<MenuAdjustEvent>,
+    mut settings: ResMut<Settings>,
+    localization: Res<Localization>,
+    page: Option<ResMut<PlayersPage>>,
+    mut menus: Query<&mut Menu, With<PlayersElement>>,
+) {
+    let mut page = if let Some(page) = page {
+        page
+    } else {
+        return;
+    };
+    let adjustments = events
+        .iter()
+        .map(|event| (event.action.clone(), 1))
+        .chain(
+            adjust_events
+                .iter()
+                .map(|event| (event.action.clone(), event.delta)),
+        )
+        .collect::<Vec<_>>();
+    for (action, delta) in adjustments {
+        if let MenuAction::Custom(option) = action {
+            if PLAYER_OPTIONS.contains(&option) {
+                adjust_option(option, delta, &mut page, &mut settings);
+            }
+        }
+    }
+
+    if settings.is_changed() || localization.is_changed() || page.is_changed() {
+        for mut menu in menus.iter_mut() {
+            for item in menu.items.iter_mut() {
+                if let MenuAction::Custom(option) = item.action {
+                    item.label = option_label(option, page.0, &settings, &localization);
+                }
+            }
+        }
+    }
+}
+
+pub fn exit_players(mut commands: Commands) {
+    commands.remove_resource::<PlayersPage>();
+}
@@ -0,0 +1,183 @@
+use crate::{
+    screen_depth, spawn_menu, spawn_screen_at_depth, AssetManager, EliminationRule, GameMode,
+    Localization, Menu, MenuAction, MenuAdjustEvent, MenuEvent, MenuItem, Settings,
+};
+use bevy::prelude::*;
+
+#[derive(Component, Clone)]
+pub struct RulesElement;
+
+/// The adjustable rules, by their `MenuAction::Custom` id.
+const RULES: [&str; 7] = [
+    "time_limit",
+    "score_target",
+    "rounds_to_win",
+    "lives",
+    "elimination",
+    "weapons",
+    "fuel",
+];
+
+/// The choices offered for each rule. `None` turns the rule off.
+const TIME_LIMITS: [Option<f32>; 6] = [
+    None,
+    Some(60.0),
+    Some(120.0),
+    Some(180.0),
+    Some(300.0),
+    Some(600.0),
+];
+const SCORE_TARGETS: [Option<u32>; 6] = [None, Some(5), Some(10), Some(15), Some(20), Some(30)];
+const MAX_ROUNDS_TO_WIN: u32 = 5;
+const MAX_LIVES: u32 = 9;
+
+fn rule_label(rule: &str, settings: &Settings, localization: &Localization) -> String {
+    let rules = &settings.rules;
+    let off = || localization.text("rules.off");
+    let on_off = |on: bool| localization.text(if on { "rules.on" } else { "rules.off" });
+    let (key, value) = match rule {
+        "time_limit" => (
+            "rules.time_limit",
+            rules.time_limit.map_or_else(off, |limit| {
+                let seconds = limit.round() as u32;
+                format!("{}:{:02}", seconds / 60, seconds % 60)
+            }),
+        ),
+        "score_target" => (
+            "rules.score_target",
+            rules
+                .score_target
+                .map_or_else(off, |target| target.to_string()),
+        ),
+        "rounds_to_win" => ("rules.rounds_to_win", rules.rounds_to_win.to_string()),
+        "lives" => ("rules.lives", rules.lives.to_string()),
+        "elimination" => (
+            "rules.elimination",
+            localization.text(rules.on_elimination.name_key()),
+        ),
+        "weapons" => ("rules.weapons", on_off(rules.weapons)),
+        "fuel" => ("rules.fuel", on_off(rules.fuel)),
+        _ => return rule.to_string(),
+    };
+    localization.format(key, &[("value", value)])
+}
+
+/// Step a rule forwards or backwards through its choices.
+fn adjust_rule(rule: &str, delta: i32, settings: &mut Settings) {
+    let rules = &mut settings.rules;
+    match rule {
+        "time_limit" => {
+            let current = TIME_LIMITS
+                .iter()
+                .position(|limit| *limit == rules.time_limit)
+                .unwrap_or(0);
+            rules.time_limit = TIME_LIMITS[step(current, delta, TIME_LIMITS.len())];
+        }
+        "score_target" => {
+            let current = SCORE_TARGETS
+                .iter()
+                .position(|target| *target == rules.score_target)
+                .unwrap_or(0);
+            rules.score_target = SCORE_TARGETS[step(current, delta, SCORE_TARGETS.len())];
+        }
+        "rounds_to_win" => {
+            let current = rules.rounds_to_win.clamp(1, MAX_ROUNDS_TO_WIN) as usize - 1;
+            rules.rounds_to_win = step(current, delta, MAX_ROUNDS_TO_WIN as usize) as u32 + 1;
+        }
+        "lives" => {
+            let current = rules.lives.clamp(1, MAX_LIVES) as usize - 1;
+            rules.lives = step(current, delta, MAX_LIVES as usize) as u32 + 1;
+        }
+        "elimination" => {
+            let all = EliminationRule::ALL;
+            let current = all
+                .iter()
+                .position(|rule| *rule == rules.on_elimination)
+                .unwrap_or(0);
+            rules.on_elimination = all[step(current, delta, all.len())];
+        }
+        "weapons" => rules.weapons = !rules.weapons,
+        "fuel" => rules.fuel = !rules.fuel,
+        _ => {}
+    }
+}
+
+/// Move `delta` places through a list of `len` values, wrapping around.
+fn step(current: usize, delta: i32, len: usize) -> usize {
+    (current as i32 + delta).rem_euclid(len as i32) as usize
+}
+
+pub fn setup_rules(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    app_state: Res<State<GameMode>>,
+) {
+    let depth = screen_depth(&app_state);
+    spawn_screen_at_depth(
+        &mut commands,
+        &assets,
+        &localization,
+        "rules",
+        depth,
+        RulesElement,
+    );
+
+    let mut items: Vec<MenuItem> = RULES
+        .iter()
+        .map(|rule| {
+            MenuItem::new(
+                rule_label(rule, &settings, &localization),
+                MenuAction::Custom(rule),
+            )
+        })
+        .collect();
+    items.push(MenuItem::new("menu.back", MenuAction::Back));
+    let menu = Menu::new(items).with_back(MenuAction::Back);
+    spawn_menu(
+        &mut commands,
+        &assets,
+        &localization,
+        menu,
+        Vec3::new(0.0, 100.0, depth + 2.0),
+        RulesElement,
+    );
+}
+
+/// Like the options screen: choosing a rule steps it forwards, and left
+/// and right step it either way. The new rules apply from the next match.
+pub fn update_rules(
+    mut events: EventReader<MenuEvent>,
+    mut adjust_events: EventReader<MenuAdjustEvent>,
+    mut settings: ResMut<Settings>,
+    localization: Res<Localization>,
+    mut menus: Query<&mut Menu, With<RulesElement>>,
+) {
+    let adjustments = events
+        .iter()
+        .map(|event| (event.action.clone(), 1))
+        .chain(
+            adjust_events
+                .iter()
+                .map(|event| (event.action.clone(), event.delta)),
+        )
+        .collect::<Vec<_>>();
+    for (action, delta) in adjustments {
+        if let MenuAction::Custom(rule) = action {
+            if RULES.contains(&rule) {
+                adjust_rule(rule, delta, &mut settings);
+            }
+        }
+    }
+
+    if settings.is_changed() || localization.is_changed() {
+        for mut menu in menus.iter_mut() {
+            for item in menu.items.iter_mut() {
+                if let MenuAction::Custom(rule) = item.action {
+                    item.label = rule_label(rule, &settings, &localization);
+                }
+            }
+        }
+    }
+}
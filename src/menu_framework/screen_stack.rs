@@ -0,0 +1,149 @@
+use super::input_guard::MinDisplayTimes;
+use super::transition::{CrossFading, ScreenTransition, TransitionSettings, TransitionStyle};
+use crate::{remove_matching_elements, GameMode};
+use bevy::{ecs::schedule::IntoSystemDescriptor, prelude::*};
+
+/// How far in front of the screen beneath it each overlay is drawn.
+const OVERLAY_DEPTH_STEP: f32 = 10.0;
+
+/// Requests a change to the screen stack. Screens are `GameMode`s; the
+/// screen on top of the stack is the one being updated, and the ones
+/// beneath it are paused (their entities stay, their systems don't run).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScreenCommand {
+    /// Open a screen on top of the current one.
+    Push(GameMode),
+    /// Close the top screen, resuming the one beneath it.
+    Pop,
+    /// Close every screen and open this one. Replacing a stack with the
+    /// screen at its bottom restarts that screen.
+    Replace(GameMode),
+}
+
+/// Carry out the first `ScreenCommand` sent this frame. Replacing the
+/// stack runs a transition (see `TransitionSettings`); commands sent while
+/// one is running are ignored.
+pub fn apply_screen_commands(
+    mut commands: EventReader<ScreenCommand>,
+    mut app_state: ResMut<State<GameMode>>,
+    mut transition: ResMut<ScreenTransition>,
+    settings: Res<TransitionSettings>,
+) {
+    // Later commands were issued by the screen that's about to change.
+    let mut handled = transition.is_active();
+    for command in commands.iter() {
+        if handled {
+            continue;
+        }
+        handled = true;
+        let result = match (command, settings.style) {
+            (ScreenCommand::Push(mode), _) => app_state.push(*mode),
+            (ScreenCommand::Pop, _) => app_state.pop(),
+            (ScreenCommand::Replace(mode), TransitionStyle::Cut) => app_state.replace(*mode),
+            (ScreenCommand::Replace(mode), TransitionStyle::Fade) => {
+                transition.fade_to(*mode);
+                Ok(())
+            }
+            (ScreenCommand::Replace(mode), TransitionStyle::CrossFade) => {
+                transition.cross_fade();
+                app_state.replace(*mode)
+            }
+        };
+        if let Err(e) = result {
+            warn!("Screen command {:?} failed: {:?}", command, e);
+        }
+    }
+}
+
+/// The z a screen should draw at, so that overlays are in front of the
+/// screens beneath them.
+pub fn screen_depth(app_state: &State<GameMode>) -> f32 {
+    app_state.inactives().len() as f32 * OVERLAY_DEPTH_STEP
+}
+
+/// Collects the systems that make up a screen, for `App::add_screen`.
+pub struct Screen {
+    mode: GameMode,
+    min_display: f32,
+    enter: SystemSet,
+    update: SystemSet,
+    exit: SystemSet,
+}
+
+impl Screen {
+    pub fn new(mode: GameMode) -> Self {
+        Self {
+            mode,
+            min_display: 0.0,
+            enter: SystemSet::on_enter(mode),
+            update: SystemSet::on_update(mode),
+            exit: SystemSet::on_exit(mode),
+        }
+    }
+
+    /// A system to run when the screen opens.
+    pub fn on_enter<Params>(mut self, system: impl IntoSystemDescriptor<Params>) -> Self {
+        self.enter = self.enter.with_system(system);
+        self
+    }
+
+    /// A system to run every frame while the screen is on top of the stack.
+    pub fn on_update<Params>(mut self, system: impl IntoSystemDescriptor<Params>) -> Self {
+        self.update = self.update.with_system(system);
+        self
+    }
+
+    /// A system to run when the screen closes.
+    pub fn on_exit<Params>(mut self, system: impl IntoSystemDescriptor<Params>) -> Self {
+        self.exit = self.exit.with_system(system);
+        self
+    }
+
+    /// Ignore menu and player input until the screen has been shown for
+    /// this many seconds.
+    pub fn with_min_display(mut self, seconds: f32) -> Self {
+        self.min_display = seconds;
+        self
+    }
+
+    /// Remove every entity tagged with `T` when the screen closes (or, in
+    /// a cross-fade, once it has faded away).
+    pub fn with_cleanup<T: Component>(self) -> Self {
+        self.on_exit(cleanup_screen::<T>)
+    }
+}
+
+fn cleanup_screen<T: Component>(
+    elements: Query<(Entity, &T)>,
+    transition: Res<ScreenTransition>,
+    mut commands: Commands,
+) {
+    if transition.is_cross_fading() {
+        for (entity, _) in elements.iter() {
+            commands
+                .entity(entity)
+                .remove::<T>()
+                .insert(CrossFading::new());
+        }
+    } else {
+        remove_matching_elements(elements, &mut commands);
+    }
+}
+
+pub trait AddScreen {
+    fn add_screen(&mut self, screen: Screen) -> &mut Self;
+}
+
+impl AddScreen for App {
+    fn add_screen(&mut self, screen: Screen) -> &mut Self {
+        if screen.min_display > 0.0 {
+            self.world
+                .get_resource_or_insert_with(MinDisplayTimes::default)
+                .0
+                .insert(screen.mode, screen.min_display);
+        }
+        self.add_system_set(screen.enter)
+            .add_system_set(screen.update)
+            .add_system_set(screen.exit)
+    }
+}
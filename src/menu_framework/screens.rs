@@ -0,0 +1,101 @@
+use crate::{centered_text_bundle, AssetManager, Localization, ScreenFill};
+use bevy::prelude::*;
+
+/// Text drawn from a string key, which follows the language setting.
+#[derive(Component)]
+pub struct LocalizedText {
+    pub key: String,
+}
+
+/// Spawn the background registered under `name`, tagging every entity with
+/// `marker` so the screen's exit system can clean it up. The background's
+/// text is treated as string keys, and shown in the current language.
+pub fn spawn_screen<T: Component + Clone>(
+    commands: &mut Commands,
+    assets: &AssetManager,
+    localization: &Localization,
+    name: &str,
+    marker: T,
+) {
+    spawn_screen_at_depth(commands, assets, localization, name, 0.0, marker);
+}
+
+/// Like `spawn_screen`, but drawn at the given z - overlays use this to
+/// sit in front of the screen beneath them.
+pub fn spawn_screen_at_depth<T: Component + Clone>(
+    commands: &mut Commands,
+    assets: &AssetManager,
+    localization: &Localization,
+    name: &str,
+    depth: f32,
+    marker: T,
+) {
+    let screen = if let Some(screen) = assets.screens.get(name) {
+        screen
+    } else {
+        warn!("No screen named \"{}\" has been registered.", name);
+        return;
+    };
+
+    match &screen.fill {
+        ScreenFill::Atlas { atlas, index } => {
+            commands
+                .spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: atlas.clone(),
+                    sprite: TextureAtlasSprite::new(*index),
+                    transform: Transform::from_xyz(0.0, 0.0, depth),
+                    ..Default::default()
+                })
+                .insert(marker.clone());
+        }
+        ScreenFill::Solid { color, size } => {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: *color,
+                        custom_size: Some(*size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, depth),
+                    ..Default::default()
+                })
+                .insert(marker.clone());
+        }
+    }
+
+    for line in screen.text.iter() {
+        let text = localization.text(&line.text);
+        commands
+            .spawn_bundle(centered_text_bundle(
+                &text,
+                assets.font_for(&text),
+                line.font_size,
+                line.color,
+                line.position + Vec3::new(0.0, 0.0, depth + 1.0),
+            ))
+            .insert(LocalizedText {
+                key: line.text.clone(),
+            })
+            .insert(marker.clone());
+    }
+}
+
+/// Re-translate screen text when the language changes, so screens that
+/// stay up underneath the options screen follow along.
+pub fn update_localized_text(
+    localization: Option<Res<Localization>>,
+    assets: Option<Res<AssetManager>>,
+    mut texts: Query<(&LocalizedText, &mut Text)>,
+) {
+    let (localization, assets) = match (localization, assets) {
+        (Some(localization), Some(assets)) if localization.is_changed() => (localization, assets),
+        _ => return,
+    };
+    for (localized, mut text) in texts.iter_mut() {
+        let value = localization.text(&localized.key);
+        if text.sections[0].value != value {
+            text.sections[0].style.font = assets.font_for(&value);
+            text.sections[0].value = value;
+        }
+    }
+}
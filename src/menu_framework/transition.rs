@@ -0,0 +1,221 @@
+use super::camera::PLAYFIELD_HEIGHT;
+use crate::{ActionInput, GameMode};
+use bevy::prelude::*;
+
+/// Drawn in front of everything except the fade overlay.
+const CROSS_FADE_DEPTH: f32 = 500.0;
+const OVERLAY_DEPTH: f32 = 900.0;
+
+/// How `ScreenCommand::Replace` changes screens. Pushing and popping
+/// overlays (pause, confirm, options) is always instant.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionStyle {
+    /// Switch immediately.
+    Cut,
+    /// Fade to black, switch, then fade back in.
+    Fade,
+    /// Switch immediately, and dissolve the old screen away on top of the
+    /// new one. Best suited to menus: the old screen's entities keep their
+    /// components until they're gone.
+    CrossFade,
+}
+
+/// Insert this resource before adding `BasicGamePlugin` to change the
+/// default fade.
+#[derive(Clone, Copy, Debug)]
+pub struct TransitionSettings {
+    pub style: TransitionStyle,
+    /// Seconds for the whole transition (both halves of a fade).
+    pub duration: f32,
+}
+
+impl Default for TransitionSettings {
+    fn default() -> Self {
+        Self {
+            style: TransitionStyle::Fade,
+            duration: 0.5,
+        }
+    }
+}
+
+enum Phase {
+    Idle,
+    FadingOut { target: GameMode },
+    FadingIn,
+    CrossFading,
+}
+
+/// The transition in progress, if any. While one is running, further
+/// screen commands are ignored and menus and players get no input.
+pub struct ScreenTransition {
+    phase: Phase,
+    elapsed: f32,
+}
+
+impl Default for ScreenTransition {
+    fn default() -> Self {
+        Self {
+            phase: Phase::Idle,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl ScreenTransition {
+    pub fn is_active(&self) -> bool {
+        !matches!(self.phase, Phase::Idle)
+    }
+
+    pub(crate) fn is_cross_fading(&self) -> bool {
+        matches!(self.phase, Phase::CrossFading)
+    }
+
+    pub(crate) fn fade_to(&mut self, target: GameMode) {
+        self.phase = Phase::FadingOut { target };
+        self.elapsed = 0.0;
+    }
+
+    pub(crate) fn cross_fade(&mut self) {
+        self.phase = Phase::CrossFading;
+        self.elapsed = 0.0;
+    }
+}
+
+/// The one camera, which lives for the whole game. Screens no longer
+/// spawn their own.
+#[derive(Component)]
+pub struct MainCamera;
+
+/// A black sprite covering the screen, faded in and out.
+#[derive(Component)]
+pub struct FadeOverlay;
+
+/// An entity from the previous screen, dissolving during a cross-fade.
+#[derive(Component)]
+pub struct CrossFading {
+    original_alpha: Option<f32>,
+}
+
+impl CrossFading {
+    pub(crate) fn new() -> Self {
+        Self {
+            original_alpha: None,
+        }
+    }
+}
+
+pub fn setup_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(MainCamera);
+
+    // Oversized, so it covers the window whatever its shape.
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+                custom_size: Some(Vec2::splat(PLAYFIELD_HEIGHT * 4.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, OVERLAY_DEPTH),
+            ..Default::default()
+        })
+        .insert(FadeOverlay);
+}
+
+#[allow(clippy::type_complexity)]
+pub fn run_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<TransitionSettings>,
+    mut transition: ResMut<ScreenTransition>,
+    mut app_state: ResMut<State<GameMode>>,
+    mut overlay: Query<&mut Sprite, With<FadeOverlay>>,
+    mut fading: Query<
+        (
+            Entity,
+            &mut CrossFading,
+            &mut Transform,
+            Option<&mut Sprite>,
+            Option<&mut TextureAtlasSprite>,
+            Option<&mut Text>,
+        ),
+        Without<FadeOverlay>,
+    >,
+) {
+    if !transition.is_active() {
+        return;
+    }
+    transition.elapsed += time.delta_seconds();
+    let duration = settings.duration.max(0.001);
+
+    match transition.phase {
+        Phase::Idle => {}
+        Phase::FadingOut { target } => {
+            let progress = (transition.elapsed / (duration / 2.0)).min(1.0);
+            set_overlay_alpha(&mut overlay, progress);
+            if progress >= 1.0 {
+                if let Err(e) = app_state.replace(target) {
+                    warn!("Unable to change to {:?}: {:?}", target, e);
+                }
+                transition.phase = Phase::FadingIn;
+                transition.elapsed = 0.0;
+            }
+        }
+        Phase::FadingIn => {
+            let progress = (transition.elapsed / (duration / 2.0)).min(1.0);
+            set_overlay_alpha(&mut overlay, 1.0 - progress);
+            if progress >= 1.0 {
+                transition.phase = Phase::Idle;
+            }
+        }
+        Phase::CrossFading => {
+            let progress = (transition.elapsed / duration).min(1.0);
+            for (entity, mut fade, mut transform, sprite, atlas_sprite, text) in fading.iter_mut() {
+                if progress >= 1.0 {
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+                let color = sprite
+                    .map(|sprite| &mut sprite.into_inner().color)
+                    .or_else(|| atlas_sprite.map(|sprite| &mut sprite.into_inner().color));
+                let current = match (&color, &text) {
+                    (Some(color), _) => color.a(),
+                    (None, Some(text)) => text.sections.first().map_or(1.0, |s| s.style.color.a()),
+                    (None, None) => 1.0,
+                };
+                let original = *fade.original_alpha.get_or_insert_with(|| {
+                    transform.translation.z += CROSS_FADE_DEPTH;
+                    current
+                });
+
+                let alpha = original * (1.0 - progress);
+                if let Some(color) = color {
+                    color.set_a(alpha);
+                }
+                if let Some(mut text) = text {
+                    for section in text.sections.iter_mut() {
+                        section.style.color.set_a(alpha);
+                    }
+                }
+            }
+            if progress >= 1.0 {
+                transition.phase = Phase::Idle;
+            }
+        }
+    }
+}
+
+fn set_overlay_alpha(overlay: &mut Query<&mut Sprite, With<FadeOverlay>>, alpha: f32) {
+    for mut sprite in overlay.iter_mut() {
+        sprite.color.set_a(alpha);
+    }
+}
+
+/// Players and menus get no input while the screen is changing.
+pub fn block_input_during_transitions(
+    transition: Res<ScreenTransition>,
+    mut actions: ResMut<ActionInput>,
+) {
+    actions.set_blocked(transition.is_active());
+}
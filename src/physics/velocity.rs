@@ -14,24 +14,30 @@ pub fn apply_velocity(time: Res<Time>, mut query: Query<(&mut Transform, &Veloci
     });
 }
 
+/// Scales how strongly attractors pull on something. Without one, the
+/// scale is 1.
+#[derive(Component)]
+pub struct GravityScale(pub f32);
+
 #[derive(Component)]
 pub struct Attractor {
     pub max_velocity: f32,
 }
 
 pub fn velocity_attractor_2d(
-    mut velocities: Query<(&Transform, &mut Velocity)>,
+    mut velocities: Query<(&Transform, &mut Velocity, Option<&GravityScale>)>,
     attractors: Query<&Transform, With<Attractor>>,
     time: Res<Time>,
 ) {
     let portion_of_frame = time.delta().as_millis() as f32 / 33.0;
     for attractor in attractors.iter() {
-        for (target, mut velocity) in velocities.iter_mut() {
+        for (target, mut velocity, gravity_scale) in velocities.iter_mut() {
             let distance = attractor.translation.distance_squared(target.translation);
             if distance > 0.0 {
                 let direction = attractor.translation - target.translation;
                 let normalized = direction.normalize();
-                let scaled = (normalized / distance) * 2000.0 * portion_of_frame;
+                let scale = gravity_scale.map_or(1.0, |scale| scale.0);
+                let scaled = (normalized / distance) * 2000.0 * scale * portion_of_frame;
                 velocity.0.x += scaled.x;
                 velocity.0.y += scaled.y;
             }
@@ -0,0 +1,400 @@
+//! Power-ups turn up now and then alongside the salvage. Flying into one
+//! gives the ship a status effect for a while: a shield that saves it from
+//! one collision or one fall into the black hole, a boost to its top
+//! speed, immunity to gravity, a magnet that pulls in salvage, or extra
+//! mass for ramming. Each effect shows as a coloured ring around the ship,
+//! which blinks as it's about to wear off.
+//!
+//! Effects live in a ship's `StatusEffects`, so any system that cares can
+//! query it and ask whether an effect is active.
+
+use crate::{
+    components::{Player, Salvage},
+    spawn_marker, spawn_particle_burst, spawn_popup, Arrival, AssetManager, Attractor,
+    BoundingBox2D, GravityScale, Localization, MatchRules, PlayGameElement, RandomNumbers,
+    Settings, SpawnMarker, Velocity,
+};
+use bevy::prelude::*;
+
+/// Seconds between power-ups appearing.
+pub const POWER_UP_INTERVAL: f32 = 8.0;
+/// Top speed while boosted. Ships normally top out at 5.
+pub const BOOST_SPEED: f32 = 7.5;
+/// A heavy ship's mass, where a normal one's is 1.
+const HEAVY_MASS: f32 = 3.0;
+/// A magnet pulls in salvage this close.
+const MAGNET_RANGE: f32 = 250.0;
+/// How much a magnet speeds salvage up per physics tick (of 33ms).
+const MAGNET_PULL: f32 = 0.15;
+/// A shield throws a ship out of the black hole at this distance and speed.
+const SHIELD_ESCAPE_DISTANCE: f32 = 60.0;
+const SHIELD_ESCAPE_SPEED: f32 = 5.0;
+/// Indicators blink for this many seconds before an effect wears off.
+const WARNING_TIME: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusEffect {
+    Shield,
+    Boost,
+    GravityImmunity,
+    Magnet,
+    Heavy,
+}
+
+impl StatusEffect {
+    pub const ALL: [StatusEffect; 5] = [
+        StatusEffect::Shield,
+        StatusEffect::Boost,
+        StatusEffect::GravityImmunity,
+        StatusEffect::Magnet,
+        StatusEffect::Heavy,
+    ];
+
+    /// The string key of the effect's name, shown when it's picked up.
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            StatusEffect::Shield => "power_up.shield",
+            StatusEffect::Boost => "power_up.boost",
+            StatusEffect::GravityImmunity => "power_up.gravity_immunity",
+            StatusEffect::Magnet => "power_up.magnet",
+            StatusEffect::Heavy => "power_up.heavy",
+        }
+    }
+
+    /// Seconds the effect lasts. A shield also ends when it's used.
+    pub fn duration(&self) -> f32 {
+        match self {
+            StatusEffect::Shield => 10.0,
+            StatusEffect::Boost => 8.0,
+            StatusEffect::GravityImmunity => 5.0,
+            StatusEffect::Magnet => 10.0,
+            StatusEffect::Heavy => 8.0,
+        }
+    }
+
+    /// The colour of the effect's pickup and indicator.
+    pub fn color(&self) -> Color {
+        match self {
+            StatusEffect::Shield => Color::CYAN,
+            StatusEffect::Boost => Color::ORANGE,
+            StatusEffect::GravityImmunity => Color::PURPLE,
+            StatusEffect::Magnet => Color::YELLOW,
+            StatusEffect::Heavy => Color::GRAY,
+        }
+    }
+}
+
+/// The effects on a ship, each with the time it has left. Every ship has
+/// one, empty until it picks something up.
+#[derive(Component, Default)]
+pub struct StatusEffects(Vec<(StatusEffect, Timer)>);
+
+impl StatusEffects {
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.0.iter().any(|(active, _)| *active == effect)
+    }
+
+    /// Start an effect, or restart it if it's already active.
+    pub fn add(&mut self, effect: StatusEffect) {
+        self.remove(effect);
+        self.0
+            .push((effect, Timer::from_seconds(effect.duration(), false)));
+    }
+
+    pub fn remove(&mut self, effect: StatusEffect) {
+        self.0.retain(|(active, _)| *active != effect);
+    }
+
+    /// End an effect that's used up. Returns whether it was active.
+    pub fn consume(&mut self, effect: StatusEffect) -> bool {
+        let had = self.has(effect);
+        self.remove(effect);
+        had
+    }
+
+    /// Seconds left on an effect, if it's active.
+    pub fn remaining(&self, effect: StatusEffect) -> Option<f32> {
+        self.0
+            .iter()
+            .find(|(active, _)| *active == effect)
+            .map(|(_, timer)| timer.duration().as_secs_f32() - timer.elapsed_secs())
+    }
+
+    /// How hard the ship is to push around, for collisions.
+    pub fn mass(&self) -> f32 {
+        if self.has(StatusEffect::Heavy) {
+            HEAVY_MASS
+        } else {
+            1.0
+        }
+    }
+
+    fn active(&self) -> impl Iterator<Item = StatusEffect> + '_ {
+        self.0.iter().map(|(effect, _)| *effect)
+    }
+}
+
+/// A ship whose shield has just taken a knock. It stays out of the bounce
+/// until it's no longer touching another ship.
+#[derive(Component)]
+pub struct Shielding;
+
+/// A power-up waiting to be picked up.
+#[derive(Component)]
+pub struct PowerUp(StatusEffect);
+
+/// A ring around `ship`, showing that `effect` is active.
+#[derive(Component)]
+pub struct EffectIndicator {
+    ship: Entity,
+    effect: StatusEffect,
+}
+
+pub struct PowerUpTimer(pub Timer);
+
+/// Periodically pick a spot for a random power-up and put a warning marker
+/// there, following the same rules as salvage: clear of the black hole and
+/// the ships, and only while there are fewer power-ups about than the
+/// rules allow.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_power_ups(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    time: Res<Time>,
+    mut timer: ResMut<PowerUpTimer>,
+    rng: Res<RandomNumbers>,
+    rules: Res<MatchRules>,
+    power_ups: Query<(), With<PowerUp>>,
+    markers: Query<&SpawnMarker>,
+    attractors: Query<&Transform, With<Attractor>>,
+    ships: Query<&Transform, With<Player>>,
+) {
+    timer.0.tick(time.delta());
+    if !timer.0.just_finished() {
+        return;
+    }
+    let policy = &rules.salvage_spawning;
+    let arriving = markers
+        .iter()
+        .filter(|marker| matches!(marker.arrival, Arrival::PowerUp(_)))
+        .count();
+    if power_ups.iter().count() + arriving >= policy.max_power_ups {
+        return;
+    }
+    let wells: Vec<Vec3> = attractors.iter().map(|well| well.translation).collect();
+    let ships: Vec<Vec3> = ships.iter().map(|ship| ship.translation).collect();
+    if let Some(position) = policy.find_spot(&rng, &wells, &ships) {
+        let effect = StatusEffect::ALL[rng.range(0, StatusEffect::ALL.len() as u32) as usize];
+        spawn_marker(
+            &mut commands,
+            &assets,
+            Arrival::PowerUp(effect),
+            position,
+            policy.warning_time,
+        );
+    }
+}
+
+/// Add a power-up, once its warning marker is done.
+pub fn spawn_power_up(
+    commands: &mut Commands,
+    assets: &AssetManager,
+    settings: &Settings,
+    effect: StatusEffect,
+    position: Vec3,
+    velocity: Vec3,
+) {
+    let mut sprite = TextureAtlasSprite::new(assets.sprite_index("powerup"));
+    sprite.color = effect.color();
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: assets.atlases[0].clone(),
+            sprite,
+            transform: Transform::from_translation(position),
+            ..Default::default()
+        })
+        .insert(PlayGameElement)
+        .insert(BoundingBox2D::new(16.0, 16.0))
+        .insert(Velocity(velocity))
+        .insert(PowerUp(effect));
+    spawn_particle_burst(
+        assets,
+        commands,
+        position,
+        effect.color(),
+        Color::BLACK,
+        1000.0,
+        settings.particle_density * 0.5,
+    );
+}
+
+/// Did any ship fly into a power-up? If so, start its effect and say
+/// which it was.
+pub fn collect_power_ups(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    localization: Res<Localization>,
+    settings: Res<Settings>,
+    mut ships: Query<(&Transform, &BoundingBox2D, &mut StatusEffects), With<Player>>,
+    power_ups: Query<(Entity, &PowerUp, &Transform, &BoundingBox2D)>,
+) {
+    for (entity, power_up, pos, bounds) in power_ups.iter() {
+        let collector = ships
+            .iter_mut()
+            .find(|(ship_pos, ship_bounds, _)| bounds.collides_with(pos, ship_bounds, ship_pos));
+        if let Some((ship_pos, _, mut effects)) = collector {
+            let effect = power_up.0;
+            effects.add(effect);
+            commands.entity(entity).despawn();
+            spawn_popup(
+                &mut commands,
+                &assets,
+                ship_pos.translation,
+                &localization.text(effect.name_key()),
+                effect.color(),
+            );
+            spawn_particle_burst(
+                &assets,
+                &mut commands,
+                ship_pos.translation,
+                effect.color(),
+                Color::WHITE,
+                1000.0,
+                settings.particle_density * 0.5,
+            );
+        }
+    }
+}
+
+/// Count down every effect, and end the ones that have run out. Gravity
+/// immunity is a `GravityScale` of 0 for as long as it lasts. Runs before
+/// `lose_lives`, so the scale is inserted before a ship the black hole
+/// destroyed this frame is despawned.
+pub fn wear_off_status_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ships: Query<(Entity, &mut StatusEffects, Option<&GravityScale>)>,
+) {
+    for (ship, mut effects, gravity_scale) in ships.iter_mut() {
+        effects.0.retain_mut(|(_, timer)| {
+            timer.tick(time.delta());
+            !timer.finished()
+        });
+        let immune = effects.has(StatusEffect::GravityImmunity);
+        if immune && gravity_scale.is_none() {
+            commands.entity(ship).insert(GravityScale(0.0));
+        } else if !immune && gravity_scale.is_some() {
+            commands.entity(ship).remove::<GravityScale>();
+        }
+    }
+}
+
+/// A shielded ship that touches the black hole loses its shield instead of
+/// its life, and is thrown clear. Runs before `black_hole`, which would
+/// otherwise destroy it.
+pub fn shield_from_black_hole(
+    holes: Query<(&Transform, &BoundingBox2D), With<Attractor>>,
+    mut ships: Query<
+        (
+            &mut Transform,
+            &BoundingBox2D,
+            &mut Velocity,
+            &mut StatusEffects,
+        ),
+        Without<Attractor>,
+    >,
+) {
+    for (hole_pos, hole_box) in holes.iter() {
+        for (mut pos, bounds, mut velocity, mut effects) in ships.iter_mut() {
+            if !effects.has(StatusEffect::Shield) || !hole_box.collides_with(hole_pos, bounds, &pos)
+            {
+                continue;
+            }
+            effects.consume(StatusEffect::Shield);
+            let mut out = (pos.translation - hole_pos.translation).truncate();
+            if out == Vec2::ZERO {
+                out = Vec2::Y;
+            }
+            let out = out.normalize().extend(0.0);
+            pos.translation = hole_pos.translation + out * SHIELD_ESCAPE_DISTANCE;
+            velocity.0 = out * SHIELD_ESCAPE_SPEED;
+        }
+    }
+}
+
+/// Ships with a magnet pull nearby salvage towards them. Heavier salvage
+/// comes more slowly.
+pub fn magnetise_salvage(
+    time: Res<Time>,
+    magnets: Query<(&Transform, &StatusEffects)>,
+    mut salvage: Query<(&Salvage, &Transform, &mut Velocity)>,
+) {
+    let portion_of_frame = time.delta().as_millis() as f32 / 33.0;
+    for (magnet, effects) in magnets.iter() {
+        if !effects.has(StatusEffect::Magnet) {
+            continue;
+        }
+        for (piece, pos, mut velocity) in salvage.iter_mut() {
+            let offset = magnet.translation - pos.translation;
+            if offset.length() < MAGNET_RANGE {
+                velocity.0 +=
+                    offset.normalize_or_zero() * MAGNET_PULL / piece.kind.mass() * portion_of_frame;
+            }
+        }
+    }
+}
+
+/// Keep a ring around each ship for each of its effects, one inside the
+/// next, blinking when the effect is nearly over.
+pub fn show_status_effects(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    ships: Query<(Entity, &Transform, &StatusEffects), Without<EffectIndicator>>,
+    mut indicators: Query<(
+        Entity,
+        &EffectIndicator,
+        &mut Transform,
+        &mut TextureAtlasSprite,
+    )>,
+) {
+    let mut shown = Vec::new();
+    for (entity, indicator, mut transform, mut sprite) in indicators.iter_mut() {
+        let (ship, effects) = match ships.get(indicator.ship) {
+            Ok((_, ship, effects)) if effects.has(indicator.effect) => (ship, effects),
+            _ => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+        shown.push((indicator.ship, indicator.effect));
+
+        let ring = effects
+            .active()
+            .position(|effect| effect == indicator.effect)
+            .unwrap_or(0);
+        transform.translation = ship.translation + Vec3::Z * 0.1;
+        transform.scale = Vec3::splat(1.0 + ring as f32 * 0.2);
+        let remaining = effects.remaining(indicator.effect).unwrap_or(0.0);
+        let blink_off = remaining < WARNING_TIME && !((remaining * 8.0) as u32).is_multiple_of(2);
+        sprite.color.set_a(if blink_off { 0.0 } else { 0.8 });
+    }
+
+    for (ship, transform, effects) in ships.iter() {
+        for effect in effects.active() {
+            if shown.contains(&(ship, effect)) {
+                continue;
+            }
+            let mut sprite = TextureAtlasSprite::new(assets.sprite_index("ring"));
+            sprite.color = effect.color();
+            commands
+                .spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: assets.atlases[0].clone(),
+                    sprite,
+                    transform: Transform::from_translation(transform.translation),
+                    ..Default::default()
+                })
+                .insert(EffectIndicator { ship, effect })
+                .insert(PlayGameElement);
+        }
+    }
+}
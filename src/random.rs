@@ -21,3 +21,9 @@ impl RandomNumbers {
         self.rng.lock().as_mut().unwrap().range(min, max)
     }
 }
+
+impl Default for RandomNumbers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,345 @@
+//! The kinds of salvage. Most of it is common scrap; rarer cores are worth
+//! more; heavy chunks are hard to push around and fall faster; volatile
+//! cells blow up if they touch each other, shoving away everything nearby.
+//! How often each kind turns up, and where and how it (and any power-up)
+//! appears, are part of the `MatchRules`.
+
+use crate::{
+    components::{Player, Salvage},
+    spawn_particle_burst, AnimationState, AnimationStateMachine, AssetManager, BoundingBox2D,
+    Invulnerable, PlayGameElement, RandomNumbers, Settings, StatusEffect, StatusEffects, Velocity,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Salvage and power-ups appear at least this far inside the edge of the
+/// screen.
+const SPAWN_MARGIN: u32 = 40;
+/// Random spots to try before giving up on adding something this time.
+const SPAWN_ATTEMPTS: usize = 20;
+/// How far a volatile cell's explosion reaches, and how hard it shoves
+/// something of mass 1 at its centre.
+const BLAST_RADIUS: f32 = 150.0;
+const BLAST_FORCE: f32 = 6.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SalvageKind {
+    Scrap,
+    Core,
+    Heavy,
+    Volatile,
+}
+
+impl SalvageKind {
+    pub const ALL: [SalvageKind; 4] = [
+        SalvageKind::Scrap,
+        SalvageKind::Core,
+        SalvageKind::Heavy,
+        SalvageKind::Volatile,
+    ];
+
+    /// Points for collecting it.
+    pub fn value(&self) -> u32 {
+        match self {
+            SalvageKind::Scrap => 1,
+            SalvageKind::Core => 3,
+            SalvageKind::Heavy => 2,
+            SalvageKind::Volatile => 1,
+        }
+    }
+
+    /// How hard it is to push around, where a ship's mass is 1.
+    pub fn mass(&self) -> f32 {
+        match self {
+            SalvageKind::Heavy => 3.0,
+            SalvageKind::Volatile => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    /// How strongly the black hole pulls on it, compared with a ship.
+    pub fn gravity(&self) -> f32 {
+        match self {
+            SalvageKind::Heavy => 1.6,
+            _ => 1.0,
+        }
+    }
+
+    /// How much bigger than scrap it is.
+    pub fn size(&self) -> f32 {
+        match self {
+            SalvageKind::Heavy => 1.4,
+            _ => 1.0,
+        }
+    }
+
+    /// The tint of its sprite.
+    pub fn color(&self) -> Color {
+        match self {
+            SalvageKind::Scrap => Color::WHITE,
+            SalvageKind::Core => Color::GOLD,
+            SalvageKind::Heavy => Color::SILVER,
+            SalvageKind::Volatile => Color::LIME_GREEN,
+        }
+    }
+
+    /// The start and end colours of its trail and particle bursts.
+    pub fn particle_colors(&self) -> (Color, Color) {
+        match self {
+            SalvageKind::Scrap => (Color::PINK, Color::BLACK),
+            SalvageKind::Core => (Color::GOLD, Color::ORANGE_RED),
+            SalvageKind::Heavy => (Color::SILVER, Color::DARK_GRAY),
+            SalvageKind::Volatile => (Color::LIME_GREEN, Color::TOMATO),
+        }
+    }
+}
+
+/// How often each kind of salvage turns up, relative to the others. Only
+/// set in `settings.ron`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SalvageWeights {
+    pub scrap: u32,
+    pub core: u32,
+    pub heavy: u32,
+    pub volatile: u32,
+}
+
+impl Default for SalvageWeights {
+    fn default() -> Self {
+        Self {
+            scrap: 60,
+            core: 10,
+            heavy: 15,
+            volatile: 15,
+        }
+    }
+}
+
+impl SalvageWeights {
+    pub fn weight(&self, kind: SalvageKind) -> u32 {
+        match kind {
+            SalvageKind::Scrap => self.scrap,
+            SalvageKind::Core => self.core,
+            SalvageKind::Heavy => self.heavy,
+            SalvageKind::Volatile => self.volatile,
+        }
+    }
+
+    /// Pick a kind at random, by weight. If every weight is zero, it's
+    /// all scrap.
+    pub fn pick(&self, rng: &RandomNumbers) -> SalvageKind {
+        let total: u32 = SalvageKind::ALL.iter().map(|kind| self.weight(*kind)).sum();
+        if total == 0 {
+            return SalvageKind::Scrap;
+        }
+        let mut roll = rng.range(0, total);
+        for kind in SalvageKind::ALL {
+            let weight = self.weight(kind);
+            if roll < weight {
+                return kind;
+            }
+            roll -= weight;
+        }
+        SalvageKind::Scrap
+    }
+}
+
+/// Where and how salvage and power-ups appear. Only set in `settings.ron`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SalvageSpawning {
+    /// The closest to a black hole anything appears.
+    pub well_clearance: f32,
+    /// The closest to a ship anything appears.
+    pub ship_clearance: f32,
+    /// Whether salvage starts in a circular orbit around the nearest black
+    /// hole, rather than drifting off at random.
+    pub orbital: bool,
+    /// The most salvage, counting warning markers, about at once.
+    pub max_salvage: usize,
+    /// The most power-ups, counting warning markers, about at once.
+    pub max_power_ups: usize,
+    /// Seconds a warning marker shows before its item appears.
+    pub warning_time: f32,
+}
+
+impl Default for SalvageSpawning {
+    fn default() -> Self {
+        Self {
+            well_clearance: 150.0,
+            ship_clearance: 120.0,
+            orbital: false,
+            max_salvage: 8,
+            max_power_ups: 2,
+            warning_time: 1.0,
+        }
+    }
+}
+
+impl SalvageSpawning {
+    /// Is `spot` far enough from all the wells and ships?
+    pub fn allows(&self, spot: Vec3, wells: &[Vec3], ships: &[Vec3]) -> bool {
+        wells
+            .iter()
+            .all(|well| well.truncate().distance(spot.truncate()) >= self.well_clearance)
+            && ships
+                .iter()
+                .all(|ship| ship.truncate().distance(spot.truncate()) >= self.ship_clearance)
+    }
+
+    /// Try random spots on the screen until one is far enough from all the
+    /// wells and ships. None if everywhere tried was too crowded.
+    pub fn find_spot(&self, rng: &RandomNumbers, wells: &[Vec3], ships: &[Vec3]) -> Option<Vec3> {
+        (0..SPAWN_ATTEMPTS)
+            .map(|_| {
+                Vec3::new(
+                    rng.range(0, 1024 - 2 * SPAWN_MARGIN) as f32 - 512.0 + SPAWN_MARGIN as f32,
+                    rng.range(0, 768 - 2 * SPAWN_MARGIN) as f32 - 384.0 + SPAWN_MARGIN as f32,
+                    1.0,
+                )
+            })
+            .find(|spot| self.allows(*spot, wells, ships))
+    }
+}
+
+/// What a warning marker is about to turn into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arrival {
+    Salvage(SalvageKind),
+    PowerUp(StatusEffect),
+}
+
+impl Arrival {
+    fn color(&self) -> Color {
+        match self {
+            Arrival::Salvage(kind) => kind.color(),
+            Arrival::PowerUp(effect) => effect.color(),
+        }
+    }
+}
+
+/// Marks where a piece of salvage or a power-up is about to appear.
+#[derive(Component)]
+pub struct SpawnMarker {
+    pub arrival: Arrival,
+    pub timer: Timer,
+}
+
+/// Put a warning marker at `position`, which turns into `arrival` after
+/// `warning_time` seconds.
+pub fn spawn_marker(
+    commands: &mut Commands,
+    assets: &AssetManager,
+    arrival: Arrival,
+    position: Vec3,
+    warning_time: f32,
+) {
+    let mut sprite = TextureAtlasSprite::new(assets.sprite_index("ring"));
+    sprite.color = arrival.color();
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: assets.atlases[0].clone(),
+            sprite,
+            transform: Transform::from_translation(position),
+            ..Default::default()
+        })
+        .insert(PlayGameElement)
+        .insert(SpawnMarker {
+            arrival,
+            timer: Timer::from_seconds(warning_time, false),
+        });
+}
+
+/// The velocity for a circular orbit around `well`, from `position`, for
+/// something pulled with the given gravity scale. The pull is
+/// `2000 / distance²` per tick, as in `velocity_attractor_2d`, so the
+/// orbital speed is `sqrt(2000 * gravity / distance)`.
+pub fn orbital_velocity(position: Vec3, well: Vec3, gravity: f32, clockwise: bool) -> Vec3 {
+    let offset = (position - well).truncate();
+    let speed = (2000.0 * gravity / offset.length()).sqrt();
+    let along = if clockwise {
+        -offset.perp()
+    } else {
+        offset.perp()
+    };
+    (along.normalize_or_zero() * speed).extend(0.0)
+}
+
+/// Blow up volatile cells that touch each other. The blast shoves ships
+/// and salvage away from it, lighter things further.
+#[allow(clippy::type_complexity)]
+pub fn detonate_volatile_salvage(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    settings: Res<Settings>,
+    mut salvage: Query<(Entity, &Salvage, &Transform, &BoundingBox2D, &mut Velocity)>,
+    mut ships: Query<
+        (
+            &Transform,
+            &StatusEffects,
+            &mut Velocity,
+            &mut AnimationStateMachine,
+        ),
+        (With<Player>, Without<Salvage>, Without<Invulnerable>),
+    >,
+) {
+    let cells: Vec<(Entity, &Transform, &BoundingBox2D)> = salvage
+        .iter()
+        .filter(|(_, piece, _, _, _)| piece.kind == SalvageKind::Volatile)
+        .map(|(entity, _, pos, bounds, _)| (entity, pos, bounds))
+        .collect();
+    let mut detonated = Vec::new();
+    let mut blasts = Vec::new();
+    for (i, (a, pos_a, box_a)) in cells.iter().enumerate() {
+        for (b, pos_b, box_b) in cells.iter().skip(i + 1) {
+            if detonated.contains(a) || detonated.contains(b) {
+                continue;
+            }
+            if box_a.collides_with(pos_a, box_b, pos_b) {
+                detonated.push(*a);
+                detonated.push(*b);
+                blasts.push((pos_a.translation + pos_b.translation) / 2.0);
+            }
+        }
+    }
+
+    let shove = |centre: Vec3, position: Vec3, mass: f32| {
+        let offset = (position - centre).truncate();
+        let distance = offset.length();
+        if distance >= BLAST_RADIUS {
+            return None;
+        }
+        let direction = if distance > 0.0 {
+            offset / distance
+        } else {
+            Vec2::Y
+        };
+        Some((direction * BLAST_FORCE * (1.0 - distance / BLAST_RADIUS) / mass).extend(0.0))
+    };
+    for centre in blasts {
+        for (_, piece, pos, _, mut velocity) in salvage.iter_mut() {
+            if let Some(push) = shove(centre, pos.translation, piece.kind.mass()) {
+                velocity.0 += push;
+            }
+        }
+        for (pos, effects, mut velocity, mut animation) in ships.iter_mut() {
+            if let Some(push) = shove(centre, pos.translation, effects.mass()) {
+                velocity.0 += push;
+                animation.trigger(AnimationState::Hit);
+            }
+        }
+        spawn_particle_burst(
+            &assets,
+            &mut commands,
+            centre,
+            Color::YELLOW,
+            Color::ORANGE_RED,
+            1500.0,
+            settings.particle_density,
+        );
+    }
+    for cell in detonated {
+        commands.entity(cell).despawn();
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{BotDifficulty, Localization, MatchRules, PlayerBindings};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The window sizes offered by the options screen. All are 4:3, so the
+/// 1024x768 playfield is scaled rather than cropped.
+pub const WINDOW_SIZES: [(f32, f32); 4] = [
+    (800.0, 600.0),
+    (1024.0, 768.0),
+    (1280.0, 960.0),
+    (1600.0, 1200.0),
+];
+
+/// The most players a match can have. Settings keep this many slots, so a
+/// player's choices survive playing with fewer for a while.
+pub const MAX_PLAYERS: usize = 4;
+
+/// The player colours offered by the players screen, with the string
+/// keys of their names.
+pub const PLAYER_COLORS: [(&str, Color); 8] = [
+    ("color.yellow", Color::YELLOW),
+    ("color.purple", Color::PURPLE),
+    ("color.cyan", Color::CYAN),
+    ("color.green", Color::GREEN),
+    ("color.red", Color::RED),
+    ("color.orange", Color::ORANGE),
+    ("color.pink", Color::PINK),
+    ("color.white", Color::WHITE),
+];
+
+/// The ships players can fly: the string key of each one's name, and the
+/// prefix of its sprites and animation clips (`ship0`, `ship0_thrust` and
+/// so on).
+pub const SHIP_DESIGNS: [(&str, &str); 2] = [("ship.scout", "ship0"), ("ship.raider", "ship1")];
+
+/// One player's seat at the game: what they're called, how their ship
+/// looks, and the keys and buttons they fly it with, unless a bot flies it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerSlot {
+    /// Shown on the scoreboard. Players without one are numbered.
+    pub name: Option<String>,
+    /// The colour of the ship's trail.
+    pub color: Color,
+    /// An index into `SHIP_DESIGNS`.
+    pub ship: usize,
+    pub bindings: PlayerBindings,
+    /// Set if the computer plays this slot, and how well.
+    #[serde(default)]
+    pub bot: Option<BotDifficulty>,
+}
+
+impl PlayerSlot {
+    pub fn default_for(player: usize) -> Self {
+        Self {
+            name: None,
+            color: PLAYER_COLORS[player % PLAYER_COLORS.len()].1,
+            ship: player % SHIP_DESIGNS.len(),
+            bindings: PlayerBindings::default_for(player),
+            bot: None,
+        }
+    }
+}
+
+/// Everything the player can change without recompiling. Loaded from
+/// `settings.ron` in the user's config directory before the app starts,
+/// and saved whenever the options screen closes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The code of the language to show text in, e.g. "en".
+    pub language: String,
+    pub window_width: f32,
+    pub window_height: f32,
+    /// Multiplies the number of particles in trails and bursts.
+    pub particle_density: f32,
+    /// How many players take part in new matches, from 2 to `MAX_PLAYERS`.
+    pub player_count: usize,
+    /// Every player slot, in use or not. Colours and ships are edited on
+    /// the players screen, and controls on the controls screen; names can
+    /// only be set in the settings file.
+    pub players: Vec<PlayerSlot>,
+    /// How far a stick must move, from 0 to 1, before it steers.
+    pub gamepad_deadzone: f32,
+    /// The rules for new matches.
+    pub rules: MatchRules,
+    /// Colours and controls from files saved before player slots, read so
+    /// `load` can move them into `players`, and never written back.
+    #[serde(skip_serializing)]
+    player_colors: Vec<Color>,
+    #[serde(skip_serializing)]
+    bindings: Vec<PlayerBindings>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: "en".to_string(),
+            window_width: 1024.0,
+            window_height: 768.0,
+            particle_density: 1.0,
+            player_count: 2,
+            players: (0..MAX_PLAYERS).map(PlayerSlot::default_for).collect(),
+            gamepad_deadzone: 0.2,
+            rules: MatchRules::default(),
+            player_colors: Vec::new(),
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("gravity_well").join("settings.ron"))
+    }
+
+    /// Load the settings file, falling back to defaults if it is missing or
+    /// can't be read.
+    pub fn load() -> Self {
+        let path = if let Some(path) = Self::path() {
+            path
+        } else {
+            return Self::default();
+        };
+        let mut settings = match std::fs::read_to_string(&path) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|e| {
+                warn!("Ignoring invalid settings in {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        // Older files may have fewer slots or actions, or an out-of-range
+        // count, or keep colours and controls outside the slots.
+        while settings.players.len() < MAX_PLAYERS {
+            let next = settings.players.len();
+            settings.players.push(PlayerSlot::default_for(next));
+        }
+        let colors = std::mem::take(&mut settings.player_colors);
+        for (slot, color) in settings.players.iter_mut().zip(colors) {
+            slot.color = color;
+        }
+        let bindings = std::mem::take(&mut settings.bindings);
+        for (slot, bindings) in settings.players.iter_mut().zip(bindings) {
+            slot.bindings = bindings;
+        }
+        for (id, slot) in settings.players.iter_mut().enumerate() {
+            slot.bindings.add_missing(id);
+        }
+        settings.player_count = settings.player_count.clamp(2, MAX_PLAYERS);
+        settings
+    }
+
+    pub fn save(&self) {
+        let path = if let Some(path) = Self::path() {
+            path
+        } else {
+            warn!("No config directory; settings were not saved.");
+            return;
+        };
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())
+            .map_err(anyhow::Error::from)
+            .and_then(|text| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::write(&path, text)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            warn!("Unable to save settings to {}: {}", path.display(), e);
+        }
+    }
+
+    /// The trail colour for a player.
+    pub fn player_color(&self, id: usize) -> Color {
+        self.players
+            .get(id)
+            .map_or(PLAYER_COLORS[id % PLAYER_COLORS.len()].1, |slot| slot.color)
+    }
+
+    /// The sprite prefix of a player's ship, e.g. "ship0".
+    pub fn player_ship(&self, id: usize) -> &'static str {
+        let design = self.players.get(id).map_or(id, |slot| slot.ship);
+        SHIP_DESIGNS[design % SHIP_DESIGNS.len()].1
+    }
+
+    /// A player's name, or "Player 3" and so on if they haven't set one.
+    pub fn player_name(&self, id: usize, localization: &Localization) -> String {
+        self.players
+            .get(id)
+            .and_then(|slot| slot.name.clone())
+            .unwrap_or_else(|| {
+                localization.format("player.default_name", &[("player", (id + 1).to_string())])
+            })
+    }
+}
+
+/// The string key for the name of a ship from `SHIP_DESIGNS`.
+pub fn ship_key(ship: usize) -> &'static str {
+    SHIP_DESIGNS[ship % SHIP_DESIGNS.len()].0
+}
+
+/// The string key for the name of a colour from `PLAYER_COLORS`.
+pub fn color_key(color: Color) -> &'static str {
+    PLAYER_COLORS
+        .iter()
+        .find(|(_, c)| *c == color)
+        .map(|(key, _)| *key)
+        .unwrap_or("color.custom")
+}
+
+/// Apply changed settings to the running game, where they take effect live.
+/// The language is applied as soon as the asset loader creates the
+/// `Localization` resource, too.
+fn apply_settings(
+    settings: Res<Settings>,
+    mut windows: ResMut<Windows>,
+    localization: Option<ResMut<Localization>>,
+) {
+    if let Some(mut localization) = localization {
+        if (settings.is_changed() || localization.is_added())
+            && localization.language() != settings.language
+        {
+            localization.set_language(&settings.language);
+        }
+    }
+    if !settings.is_changed() {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        if window.width() != settings.window_width || window.height() != settings.window_height {
+            window.set_resolution(settings.window_width, settings.window_height);
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Settings>() {
+            app.insert_resource(Settings::load());
+        }
+        app.add_system(apply_settings);
+    }
+}
@@ -0,0 +1,178 @@
+//! Ships can fire shots, when the match rules allow it. Shots are pulled
+//! by the black hole like everything else, so they curve; they fade after
+//! a while, knock back any ship they hit, and break salvage apart. Firing
+//! uses energy, which recharges over time.
+
+use crate::{
+    components::{Player, Salvage},
+    spawn_particle_burst, ActionInput, AnimationState, AnimationStateMachine, AssetManager,
+    BoundingBox2D, InputAction, Invulnerable, MatchRules, PlayGameElement, Settings, StatusEffects,
+    Velocity, ARENA_HEIGHT, ARENA_WIDTH,
+};
+use bevy::prelude::*;
+
+/// Seconds between shots while Fire is held.
+const FIRE_COOLDOWN: f32 = 0.25;
+const MAX_ENERGY: f32 = 100.0;
+const SHOT_COST: f32 = 25.0;
+/// Energy regained per second.
+const ENERGY_RECHARGE: f32 = 20.0;
+/// Shots leave at this speed, on top of the ship's own.
+const SHOT_SPEED: f32 = 8.0;
+/// Seconds a shot lasts.
+const SHOT_LIFETIME: f32 = 1.5;
+/// How hard a shot shoves the ship it hits.
+const KNOCKBACK: f32 = 1.5;
+
+/// A ship's gun.
+#[derive(Component)]
+pub struct Weapon {
+    cooldown: Timer,
+    energy: f32,
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(FIRE_COOLDOWN, false);
+        // Ready to fire straight away.
+        cooldown.tick(cooldown.duration());
+        Self {
+            cooldown,
+            energy: MAX_ENERGY,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Projectile {
+    /// The player who fired it, who can't be hit by it.
+    owner: usize,
+    lifetime: Timer,
+}
+
+/// Recharge every weapon, and fire the ones whose players are holding
+/// Fire, if they're ready and have the energy.
+pub fn fire_weapons(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<ActionInput>,
+    rules: Res<MatchRules>,
+    assets: Res<AssetManager>,
+    settings: Res<Settings>,
+    mut ships: Query<(&Player, &Transform, &Velocity, &mut Weapon)>,
+) {
+    for (player, transform, velocity, mut weapon) in ships.iter_mut() {
+        weapon.cooldown.tick(time.delta());
+        weapon.energy = (weapon.energy + ENERGY_RECHARGE * time.delta_seconds()).min(MAX_ENERGY);
+        if !rules.weapons
+            || !actions.pressed(player.id, InputAction::Fire)
+            || !weapon.cooldown.finished()
+            || weapon.energy < SHOT_COST
+        {
+            continue;
+        }
+        weapon.cooldown.reset();
+        weapon.energy -= SHOT_COST;
+
+        let facing = transform.local_y();
+        // Start clear of the ship, so the shot doesn't hit it on the way out.
+        let position = transform.translation + facing * 20.0;
+        let mut sprite = TextureAtlasSprite::new(assets.sprite_index("particle"));
+        sprite.color = settings.player_color(player.id);
+        commands
+            .spawn_bundle(SpriteSheetBundle {
+                texture_atlas: assets.atlases[0].clone(),
+                sprite,
+                transform: Transform::from_translation(position),
+                ..Default::default()
+            })
+            .insert(Projectile {
+                owner: player.id,
+                lifetime: Timer::from_seconds(SHOT_LIFETIME, false),
+            })
+            .insert(Velocity(velocity.0 + facing * SHOT_SPEED))
+            .insert(BoundingBox2D::new(8.0, 8.0))
+            .insert(PlayGameElement);
+    }
+}
+
+/// Remove shots that have run out of time or reached the edge of the
+/// arena (where `clamp_positions` would otherwise hold them).
+pub fn expire_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectiles: Query<(Entity, &mut Projectile, &Transform)>,
+) {
+    for (entity, mut projectile, transform) in projectiles.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+        let position = transform.translation;
+        if projectile.lifetime.finished()
+            || position.x.abs() >= ARENA_WIDTH / 2.0
+            || position.y.abs() >= ARENA_HEIGHT / 2.0
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Shots that hit a ship knock it back, less so if it's heavy; shots that
+/// hit salvage break it.
+/// Either way, the shot is used up.
+#[allow(clippy::type_complexity)]
+pub fn projectile_hits(
+    mut commands: Commands,
+    assets: Res<AssetManager>,
+    settings: Res<Settings>,
+    projectiles: Query<(Entity, &Projectile, &Transform, &Velocity, &BoundingBox2D)>,
+    mut ships: Query<
+        (
+            &Player,
+            &Transform,
+            &BoundingBox2D,
+            &mut Velocity,
+            &mut AnimationStateMachine,
+            &StatusEffects,
+        ),
+        (Without<Invulnerable>, Without<Projectile>),
+    >,
+    salvage: Query<(Entity, &Salvage, &Transform, &BoundingBox2D)>,
+) {
+    for (shot, projectile, shot_pos, shot_velocity, shot_box) in projectiles.iter() {
+        let hit_ship = ships.iter_mut().find(|(player, pos, bounds, _, _, _)| {
+            player.id != projectile.owner && shot_box.collides_with(shot_pos, bounds, pos)
+        });
+        if let Some((_, pos, _, mut velocity, mut animation, effects)) = hit_ship {
+            velocity.0 += shot_velocity.0.normalize_or_zero() * KNOCKBACK / effects.mass();
+            animation.trigger(AnimationState::Hit);
+            commands.entity(shot).despawn();
+            spawn_particle_burst(
+                &assets,
+                &mut commands,
+                pos.translation,
+                Color::WHITE,
+                Color::RED,
+                500.0,
+                settings.particle_density * 0.25,
+            );
+            continue;
+        }
+
+        let hit_salvage = salvage
+            .iter()
+            .find(|(_, _, pos, bounds)| shot_box.collides_with(shot_pos, bounds, pos));
+        if let Some((entity, piece, pos, _)) = hit_salvage {
+            let (start, end) = piece.kind.particle_colors();
+            commands.entity(shot).despawn();
+            commands.entity(entity).despawn();
+            spawn_particle_burst(
+                &assets,
+                &mut commands,
+                pos.translation,
+                start,
+                end,
+                800.0,
+                settings.particle_density * 0.5,
+            );
+        }
+    }
+}